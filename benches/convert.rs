@@ -0,0 +1,37 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fourier_series_animation::util::math::{
+    convert_to_fourier_series, convert_to_fourier_series_fft,
+};
+use num::Complex;
+use std::f64::consts::TAU;
+
+const SERIES_N: [usize; 5] = [11, 51, 101, 251, 501];
+
+// Five-pointed star with rounded tips, one loop over t in [0, 1]
+fn star(t: f64) -> Complex<f64> {
+    let angle = t * TAU;
+    Complex::from_polar(1.0 + 0.4 * (5.0 * angle).cos(), angle)
+}
+
+fn bench_convert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert_to_fourier_series");
+    // Large n takes long per iteration, fewer samples keep the run short
+    group.sample_size(10);
+    for n in SERIES_N {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| convert_to_fourier_series(star, black_box(n)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("convert_to_fourier_series_fft");
+    for n in SERIES_N {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| convert_to_fourier_series_fft(star, black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_convert);
+criterion_main!(benches);
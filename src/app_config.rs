@@ -0,0 +1,75 @@
+use fourier_series_animation::util::math::{DEFAULT_MAX_DEPTH, DEFAULT_TOL};
+use std::ops::RangeInclusive;
+
+// Defaults and limits of the app, gathered so that they can be tuned in one place
+// Persisted with the other settings, which still take precedence where they are stored
+// separately, e.g. the last used animation speed
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    // Progress per second
+    pub animation_speed: f64,
+    pub preview_speed: f64,
+    pub series_n: usize,
+    // Limits of the n slider
+    pub min_series_n: usize,
+    pub max_series_n: usize,
+    // Above this n the user has to confirm before computing
+    pub large_n_threshold: usize,
+    // Trace resolution windows start with
+    pub trace_points: usize,
+    // Resolution of the signal plots and the copied samples
+    pub signal_samples: usize,
+    // Points at which the series is compared against the path
+    pub error_samples: usize,
+    // Points the target outline is traced with
+    pub target_samples: usize,
+    pub export_samples: usize,
+    // Integration accuracy of the coefficients
+    pub tolerance: f64,
+    pub max_depth: usize,
+    // Multiplier of the native pixels per point
+    pub ui_scale: f32,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            animation_speed: 0.2,
+            preview_speed: 0.23,
+            series_n: 11,
+            min_series_n: 9,
+            max_series_n: 501,
+            large_n_threshold: 301,
+            trace_points: 1000,
+            signal_samples: 1000,
+            error_samples: 1000,
+            target_samples: 1000,
+            export_samples: 1000,
+            tolerance: DEFAULT_TOL,
+            max_depth: DEFAULT_MAX_DEPTH,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn series_n_range(&self) -> RangeInclusive<usize> {
+        self.min_series_n..=self.max_series_n
+    }
+
+    // Whether the values can be used as they are, e.g. after being edited by hand
+    pub fn is_valid(&self) -> bool {
+        self.animation_speed > 0.0
+            && self.preview_speed > 0.0
+            && self.min_series_n <= self.max_series_n
+            && self.series_n_range().contains(&self.series_n)
+            && self.trace_points > 0
+            && self.signal_samples > 0
+            && self.error_samples > 0
+            && self.target_samples > 0
+            && self.export_samples >= 2
+            && self.tolerance > 0.0
+            && self.ui_scale > 0.0
+    }
+}
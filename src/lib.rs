@@ -0,0 +1,3 @@
+pub mod render;
+pub mod svg_path;
+pub mod util;
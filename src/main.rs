@@ -1,26 +1,54 @@
 #![windows_subsystem = "windows"]
 
-use num::complex::Complex;
+use num::Complex;
 use std::{
-    convert::{TryFrom, TryInto},
+    iter,
     ops::{Deref, DerefMut},
-    vec,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
 };
 
 use eframe::{egui, epi};
 
+use fourier_series_animation::{
+    render,
+    svg_path::{
+        parse_path_data_into_proc_with, parse_path_data_with, parse_svg_commands_with,
+        parse_svg_into_proc_with, ParseOptions, ParseWarnings, PathFnBox,
+    },
+    util::{
+        self,
+        math::{ConvertOptions, FourierSeriesDesc, Quadrature, Sampling},
+    },
+};
+
+mod app_config;
 mod ui;
-mod util;
+
+use app_config::AppConfig;
 
 use ui::{
     frame_history::FrameHistory,
+    path_editor::PathEditor,
+    playlist::Playlist,
+    plot_theme::{PlotTheme, StrokeStyle},
     svg_select::SvgSelect,
-    window::{fourier_animation::FourierAnimationWindow, svg_preview::SvgPreviewWindow, Window},
+    window::{
+        fourier_animation::{FourierAnimationWindow, PlaylistEntry},
+        svg_preview::SvgPreviewWindow,
+        UiTiming, Window,
+    },
 };
+use util::transform::PlaneTransform;
 
 struct WindowDesc<T: ui::window::Window> {
     is_open: bool,
     window: T,
+    timing: UiTiming,
 }
 
 impl<T: Window> Deref for WindowDesc<T> {
@@ -37,212 +65,566 @@ impl<T: Window> DerefMut for WindowDesc<T> {
     }
 }
 
-impl<T: Window + Default> Default for WindowDesc<T> {
-    fn default() -> Self {
+impl<T: Window> WindowDesc<T> {
+    fn new(window: T) -> Self {
         Self {
             is_open: false,
-            window: Default::default(),
+            window,
+            timing: Default::default(),
         }
     }
-}
 
-impl<T: Window> WindowDesc<T> {
-    fn show(&mut self, ctx: &egui::CtxRef) -> bool {
-        self.window.show(ctx, &mut self.is_open)
+    fn show(&mut self, ctx: &egui::CtxRef, show_timing: bool) -> bool {
+        let timing = Some(&mut self.timing).filter(|_| show_timing);
+        self.window.show(ctx, &mut self.is_open, timing)
     }
 }
 
+struct CalculatedSeries {
+    // File name of the source path
+    name: String,
+    desc: FourierSeriesDesc<f64>,
+    approximation_error: f64,
+    // Source path traced over the full period
+    target: Vec<Complex<f64>>,
+    warnings: ParseWarnings,
+    // (chosen n, whether the error target was met) if n was fitted
+    fitted_n: Option<(usize, bool)>,
+}
+
+// How the number of coefficients is chosen
+#[derive(Clone, Copy)]
+enum SeriesN {
+    Fixed(usize),
+    // Smallest n up to max_n within max_error of the path
+    Fit { max_n: usize, max_error: f64 },
+}
+
+// What the animation does with calculated series
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SeriesTarget {
+    Replace,
+    // Draw along with the current animation
+    Append,
+    // Draw one after another
+    Playlist,
+}
+
+// Series calculation running in the background
+struct PendingSeries {
+    // Series of each file, in order
+    receiver: Receiver<Result<Vec<CalculatedSeries>, String>>,
+    // Number of coefficients finished so far, out of n
+    progress: Arc<AtomicUsize>,
+    // Changes as series of increasing n are tried while fitting
+    n: Arc<AtomicUsize>,
+    // Index of the file being calculated, out of files_count
+    file: Arc<AtomicUsize>,
+    files_count: usize,
+    target: SeriesTarget,
+}
+
 struct MyApp {
+    config: AppConfig,
     frame_history: FrameHistory,
+    // Show how long each window takes to lay out, for profiling heavy series
+    show_window_timings: bool,
     animation_window: WindowDesc<FourierAnimationWindow>,
     svg_select: SvgSelect,
     svg_preview_window: WindowDesc<SvgPreviewWindow>,
+    playlist: Playlist,
+    path_editor: PathEditor,
     fourier_series_n: usize,
+    export_samples_count: usize,
+    status_msg: Option<String>,
+    large_n_confirmed: bool,
+    plot_theme: PlotTheme,
+    // egui starts out dark
+    dark_mode: bool,
+    // Multiplier of the native pixels per point
+    ui_scale: f32,
+    mirror_x: bool,
+    // Show shapes upright as in SVG viewers
+    flip_y: bool,
+    parse_options: ParseOptions,
+    convert_options: ConvertOptions,
+    use_fft: bool,
+    // Where the path is sampled for the FFT
+    sampling: Sampling,
+    // Choose n automatically, the slider giving the largest n tried
+    fit_n: bool,
+    // RMS error n is fitted to
+    fit_max_error: f64,
+    pending_series: Option<PendingSeries>,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
+        Self::new(AppConfig::default())
+    }
+}
+
+impl MyApp {
+    fn new(config: AppConfig) -> Self {
         Self {
             frame_history: Default::default(),
-            animation_window: Default::default(),
+            show_window_timings: false,
+            animation_window: WindowDesc::new(FourierAnimationWindow::new(&config)),
             svg_select: Default::default(),
-            svg_preview_window: Default::default(),
-            fourier_series_n: 11,
+            svg_preview_window: WindowDesc::new(SvgPreviewWindow::new(&config)),
+            playlist: Default::default(),
+            path_editor: Default::default(),
+            fourier_series_n: config.series_n,
+            export_samples_count: config.export_samples,
+            status_msg: None,
+            large_n_confirmed: false,
+            plot_theme: Default::default(),
+            dark_mode: true,
+            ui_scale: config.ui_scale,
+            mirror_x: false,
+            flip_y: false,
+            parse_options: Default::default(),
+            convert_options: ConvertOptions {
+                tolerance: config.tolerance,
+                max_depth: config.max_depth,
+                ..Default::default()
+            },
+            use_fft: false,
+            sampling: Default::default(),
+            fit_n: false,
+            fit_max_error: 1e-3,
+            pending_series: None,
+            config,
         }
     }
 }
 
-fn cubic_bezier(
-    p0: Complex<f64>,
-    p1: Complex<f64>,
-    p2: Complex<f64>,
-    p3: Complex<f64>,
-    t: f64,
-) -> Complex<f64> {
-    let inv_t = 1.0 - t;
-    inv_t.powi(3) * p0
-        + 3.0 * inv_t.powi(2) * t * p1
-        + 3.0 * inv_t * t.powi(2) * p2
-        + t.powi(3) * p3
+fn is_samples_path(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|s| s == "csv" || s == "txt")
 }
 
-#[derive(Debug)]
-enum CmdData {
-    Move(Complex<f64>),
-    CubicCurve(Complex<f64>, Complex<f64>, Complex<f64>),
+// Path function of a loaded file along with what is known about its structure
+struct LoadedPath {
+    proc: PathFnBox,
+    // Set if the path consists of segments each taking an equal share of t
+    segments_count: Option<usize>,
+    // Parts of an SVG that were left out
+    warnings: ParseWarnings,
+    // Points of a point list, which the series is computed from directly
+    points: Option<Vec<Complex<f64>>>,
 }
 
-#[derive(thiserror::Error, Debug)]
-enum TryFromCommandError {
-    #[error("Found unrecognized command `{0:?}`")]
-    UnrecognizedCommand(String),
-    #[error("Parameters is invalid")]
-    InvalidParameter,
-}
-
-struct VecCmdData(Vec<CmdData>);
-
-impl TryFrom<&svg::node::element::path::Command> for VecCmdData {
-    type Error = TryFromCommandError;
-
-    fn try_from(value: &svg::node::element::path::Command) -> Result<Self, Self::Error> {
-        use svg::node::element::path::{Command, Position::Absolute};
-
-        let result = match value {
-            Command::Move(Absolute, param) => {
-                if param.len() != 2 {
-                    return Err(Self::Error::InvalidParameter);
-                }
-
-                vec![CmdData::Move(Complex::new(
-                    param[0].into(),
-                    param[1].into(),
-                ))]
-            }
-            Command::CubicCurve(Absolute, param) => {
-                if param.len() % 6 != 0 {
-                    return Err(Self::Error::InvalidParameter);
-                }
-
-                let mut vec_result = Vec::new();
-                for s in param.chunks_exact(6) {
-                    let p1 = Complex::new(s[0].into(), s[1].into());
-                    let p2 = Complex::new(s[2].into(), s[3].into());
-                    let p3 = Complex::new(s[4].into(), s[5].into());
-                    vec_result.push(CmdData::CubicCurve(p1, p2, p3));
-                }
-
-                vec_result
-            }
-            Command::Close => vec![],
-            other_cmd => return Err(Self::Error::UnrecognizedCommand(format!("{:?}", other_cmd))),
+// Loads either an SVG or a CSV/text file of exported samples or points
+fn parse_into_proc<T: AsRef<std::path::Path>>(
+    path: T,
+    options: &ParseOptions,
+) -> Result<LoadedPath, String> {
+    use util::samples::SampleData;
+
+    if is_samples_path(path.as_ref()) {
+        let data = util::samples::read_sample_data(path).map_err(|e| e.to_string())?;
+        let (proc, points) = match data {
+            SampleData::Timed(samples) => (util::samples::samples_into_proc(samples), None),
+            SampleData::Points(points) => (
+                util::samples::points_into_proc(points.clone()),
+                Some(points),
+            ),
         };
-
-        Ok(VecCmdData(result))
+        Ok(LoadedPath {
+            proc,
+            segments_count: None,
+            warnings: ParseWarnings::default(),
+            points,
+        })
+    } else {
+        let (proc, segments_count, warnings) =
+            parse_svg_into_proc_with(path, options).map_err(|e| e.to_string())?;
+        Ok(LoadedPath {
+            proc,
+            // Segments no longer start at k / count once t is mapped by arc length
+            segments_count: Some(segments_count).filter(|_| !options.arc_length),
+            warnings,
+            points: None,
+        })
     }
 }
 
-fn parse_svg_into_proc<T: AsRef<std::path::Path>>(
-    path: T,
-) -> Option<Box<dyn Fn(f64) -> Complex<f64>>> {
-    use svg::node::element::path::Data;
-    use svg::node::element::tag::Path;
-    use svg::parser::Event;
-
-    let mut content = String::new();
-
-    let mut cmd_vec: Vec<CmdData> = Vec::new();
-    let mut segments_count: usize = 0;
-
-    for event in svg::open(path, &mut content).unwrap() {
-        match event {
-            Event::Tag(Path, _, attributes) => {
-                let data = attributes.get("d")?;
-                let data = Data::parse(data).ok()?;
-                for command in data.iter() {
-                    match command.try_into() {
-                        Ok(data) => {
-                            let mut data: VecCmdData = data;
-                            cmd_vec.append(&mut data.0);
-                        }
-                        Err(e) => {
-                            eprintln!("SVG parse error: {}", e);
-                            return None;
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-
-    for i in &cmd_vec {
-        if let CmdData::Move(..) = i {
-            // Move is not considered a segment
-        } else {
-            segments_count += 1;
-        }
+// Reads either a saved series or frequency/amplitude/phase vectors
+fn load_series_json(path: &std::path::Path) -> Result<FourierSeriesDesc<f64>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    if value.get("coefficients").is_some() {
+        FourierSeriesDesc::from_json(&content).map_err(|e| e.to_string())
+    } else {
+        util::import::series_from_vectors_json(&content).map_err(|e| e.to_string())
     }
+}
 
-    // println!("Parsed SVG: {:#?}", cmd_vec);
-    // println!("Total {} segment(s).", segments_count);
-
-    let func = move |t| {
-        let idx_prog = t * segments_count as f64;
-        let idx = idx_prog as usize;
-        let prog = idx_prog - idx as f64;
-
-        let mut cur_pos = Complex::new(0.0, 0.0);
-        let mut cur_idx = 0;
-        for cmd in &cmd_vec {
-            match cmd {
-                CmdData::Move(p0) => {
-                    cur_pos = *p0;
-                }
-                CmdData::CubicCurve(p1, p2, p3) => {
-                    cur_idx += 1;
-                    if cur_idx > idx {
-                        return cubic_bezier(cur_pos, *p1, *p2, *p3, prog);
+const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+// Computes the series of the files at paths one after another on a worker thread
+fn spawn_calculation(
+    paths: Vec<String>,
+    series_n: SeriesN,
+    // Sampling of the path if the FFT is used instead of integration
+    fft_sampling: Option<Sampling>,
+    parse_options: ParseOptions,
+    convert_options: ConvertOptions,
+    target: SeriesTarget,
+    config: &AppConfig,
+) -> PendingSeries {
+    let (error_samples, target_samples) = (config.error_samples, config.target_samples);
+    let files_count = paths.len();
+    let progress = Arc::new(AtomicUsize::new(0));
+    let current_n = Arc::new(AtomicUsize::new(0));
+    let current_file = Arc::new(AtomicUsize::new(0));
+    let convert_options = ConvertOptions {
+        progress: Some(progress.clone()),
+        ..convert_options
+    };
+    let (sender, receiver) = mpsc::channel();
+    let (worker_progress, worker_n, worker_file) =
+        (progress.clone(), current_n.clone(), current_file.clone());
+    let calculate = move |path: &String| {
+        let name = std::path::Path::new(path)
+            .file_name()
+            .map_or_else(|| path.clone(), |s| s.to_string_lossy().into_owned());
+        parse_into_proc(path, &parse_options).and_then(|loaded| {
+            let LoadedPath {
+                proc,
+                warnings,
+                points,
+                ..
+            } = loaded;
+            let compute = |n| {
+                worker_progress.store(0, Ordering::Relaxed);
+                worker_n.store(n, Ordering::Relaxed);
+                match &points {
+                    // The points are the best samples there are; fewer than n would alias
+                    Some(points) if points.len() >= n => {
+                        FourierSeriesDesc::from_samples(points, n, convert_options.max_n)
                     }
-                    cur_pos = *p3;
+                    _ => match fft_sampling {
+                        Some(sampling) => util::math::convert_to_fourier_series_sampled(
+                            &proc,
+                            n,
+                            sampling,
+                            convert_options.max_n,
+                        ),
+                        None => {
+                            util::math::convert_to_fourier_series_with(&proc, n, &convert_options)
+                        }
+                    },
                 }
-            }
-        }
-
-        cur_pos
+                .map_err(|e| e.to_string())
+            };
+            let (desc, fitted_n) = match series_n {
+                SeriesN::Fixed(n) => (compute(n)?, None),
+                SeriesN::Fit { max_n, max_error } => {
+                    let (desc, met) =
+                        util::math::fit_series(&proc, max_n, max_error, error_samples, compute)?;
+                    let n = desc.as_vec().len();
+                    (desc, Some((n, met)))
+                }
+            };
+            let approximation_error = desc.approximation_error(&proc, error_samples);
+            let target = (0..=target_samples)
+                .map(|i| proc(i as f64 / target_samples as f64))
+                .collect();
+            Ok(CalculatedSeries {
+                name,
+                desc,
+                approximation_error,
+                target,
+                warnings,
+                fitted_n,
+            })
+        })
     };
+    thread::spawn(move || {
+        let result = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                worker_file.store(i, Ordering::Relaxed);
+                calculate(path).map_err(|e| {
+                    if files_count > 1 {
+                        format!("{}: {}", path, e)
+                    } else {
+                        e
+                    }
+                })
+            })
+            .collect();
+        // The app may have been closed in the meantime
+        let _ = sender.send(result);
+    });
+    PendingSeries {
+        receiver,
+        progress,
+        n: current_n,
+        file: current_file,
+        files_count,
+        target,
+    }
+}
 
-    Some(Box::new(func))
+const RECENT_FILES_KEY: &str = "recent_files";
+const LAST_PATH_KEY: &str = "last_path";
+const SERIES_N_KEY: &str = "fourier_series_n";
+const ANIMATION_SPEED_KEY: &str = "animation_speed";
+const PREVIEW_SPEED_KEY: &str = "preview_speed";
+const DARK_MODE_KEY: &str = "dark_mode";
+const PLOT_THEME_KEY: &str = "plot_theme";
+const UI_SCALE_KEY: &str = "ui_scale";
+const APP_CONFIG_KEY: &str = "app_config";
+const ANIMATION_DECORATIONS_KEY: &str = "animation_plot_decorations";
+const PREVIEW_DECORATIONS_KEY: &str = "preview_plot_decorations";
+const ANIMATION_STROKE_KEY: &str = "animation_stroke_style";
+const PREVIEW_STROKE_KEY: &str = "preview_stroke_style";
+
+fn app_visuals(dark_mode: bool) -> egui::Visuals {
+    if dark_mode {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    }
 }
 
 impl epi::App for MyApp {
+    // eframe 0.14 reads this once, for the native window title and the storage key, so it
+    // cannot follow the selected file
     fn name(&self) -> &'static str {
         "Fourier Series Drawing Animation"
     }
 
+    fn setup(
+        &mut self,
+        ctx: &egui::CtxRef,
+        _frame: &mut epi::Frame<'_>,
+        storage: Option<&dyn epi::Storage>,
+    ) {
+        // Everything else is restored on top of the defaults given by the config
+        if let Some(config) = storage
+            .and_then(|s| epi::get_value::<AppConfig>(s, APP_CONFIG_KEY))
+            .filter(AppConfig::is_valid)
+        {
+            *self = Self::new(config);
+        }
+        if let Some(dark_mode) = storage.and_then(|s| epi::get_value(s, DARK_MODE_KEY)) {
+            self.dark_mode = dark_mode;
+        }
+        ctx.set_visuals(app_visuals(self.dark_mode));
+
+        let storage = match storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        if let Some(recent) = epi::get_value(storage, RECENT_FILES_KEY) {
+            self.svg_select.set_recent_files(recent);
+        }
+        // The file may have been moved or deleted since the last run
+        self.svg_select.disp_path = epi::get_value::<Option<String>>(storage, LAST_PATH_KEY)
+            .flatten()
+            .filter(|p| std::path::Path::new(p).exists());
+        if let Some(n) = epi::get_value::<usize>(storage, SERIES_N_KEY)
+            .filter(|n| self.config.series_n_range().contains(n))
+        {
+            self.fourier_series_n = n;
+        }
+        if let Some(speed) =
+            epi::get_value::<f64>(storage, ANIMATION_SPEED_KEY).filter(|&s| s > 0.0)
+        {
+            self.animation_window.set_speed(speed);
+        }
+        if let Some(speed) = epi::get_value::<f64>(storage, PREVIEW_SPEED_KEY).filter(|&s| s > 0.0)
+        {
+            self.svg_preview_window.set_speed(speed);
+        }
+        if let Some(scale) =
+            epi::get_value::<f32>(storage, UI_SCALE_KEY).filter(|s| UI_SCALE_RANGE.contains(s))
+        {
+            self.ui_scale = scale;
+        }
+        if let Some(plot_theme) = epi::get_value(storage, PLOT_THEME_KEY) {
+            self.plot_theme = plot_theme;
+        }
+        if let Some(decorations) = epi::get_value(storage, ANIMATION_DECORATIONS_KEY) {
+            self.animation_window.set_plot_decorations(decorations);
+        }
+        if let Some(decorations) = epi::get_value(storage, PREVIEW_DECORATIONS_KEY) {
+            self.svg_preview_window.set_plot_decorations(decorations);
+        }
+        if let Some(style) = epi::get_value::<StrokeStyle>(storage, ANIMATION_STROKE_KEY)
+            .filter(|s| StrokeStyle::TRACE_WIDTH_RANGE.contains(&s.trace_width))
+        {
+            self.animation_window.set_stroke_style(style);
+        }
+        if let Some(style) = epi::get_value::<StrokeStyle>(storage, PREVIEW_STROKE_KEY)
+            .filter(|s| StrokeStyle::TRACE_WIDTH_RANGE.contains(&s.trace_width))
+        {
+            self.svg_preview_window.set_stroke_style(style);
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        epi::set_value(storage, RECENT_FILES_KEY, &self.svg_select.recent_files());
+        epi::set_value(storage, LAST_PATH_KEY, &self.svg_select.disp_path);
+        epi::set_value(storage, SERIES_N_KEY, &self.fourier_series_n);
+        epi::set_value(storage, ANIMATION_SPEED_KEY, &self.animation_window.speed());
+        epi::set_value(storage, PREVIEW_SPEED_KEY, &self.svg_preview_window.speed());
+        epi::set_value(storage, DARK_MODE_KEY, &self.dark_mode);
+        epi::set_value(storage, PLOT_THEME_KEY, &self.plot_theme);
+        epi::set_value(storage, UI_SCALE_KEY, &self.ui_scale);
+        epi::set_value(storage, APP_CONFIG_KEY, &self.config);
+        epi::set_value(
+            storage,
+            ANIMATION_DECORATIONS_KEY,
+            &self.animation_window.plot_decorations(),
+        );
+        epi::set_value(
+            storage,
+            PREVIEW_DECORATIONS_KEY,
+            &self.svg_preview_window.plot_decorations(),
+        );
+        epi::set_value(
+            storage,
+            ANIMATION_STROKE_KEY,
+            &self.animation_window.stroke_style(),
+        );
+        epi::set_value(
+            storage,
+            PREVIEW_STROKE_KEY,
+            &self.svg_preview_window.stroke_style(),
+        );
+    }
+
     fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
         let Self {
+            config,
             frame_history,
+            show_window_timings,
             animation_window,
             svg_select,
             svg_preview_window,
+            playlist,
+            path_editor,
             fourier_series_n,
+            export_samples_count,
+            status_msg,
+            large_n_confirmed,
+            plot_theme,
+            dark_mode,
+            ui_scale,
+            mirror_x,
+            flip_y,
+            parse_options,
+            convert_options,
+            use_fft,
+            sampling,
+            fit_n,
+            fit_max_error,
+            pending_series,
         } = self;
 
         frame_history.on_new_frame(ctx.input().time, frame.info().cpu_usage);
 
-        if let Some(pixels_per_point) = frame.info().native_pixels_per_point {
-            ctx.set_pixels_per_point(pixels_per_point * 1.2);
+        // Rescaling while the scale slider is dragged would move it under the pointer
+        if let Some(pixels_per_point) = frame
+            .info()
+            .native_pixels_per_point
+            .filter(|_| !ctx.is_using_pointer())
+        {
+            ctx.set_pixels_per_point(pixels_per_point * *ui_scale);
+        }
+
+        if let Some(pending) = pending_series {
+            match pending.receiver.try_recv() {
+                Ok(result) => {
+                    let target = pending.target;
+                    *pending_series = None;
+                    match result {
+                        Ok(calculated) => {
+                            let mut messages = Vec::new();
+                            for series in &calculated {
+                                // Messages of several files are told apart by name
+                                let prefix = if calculated.len() > 1 {
+                                    format!("{}: ", series.name)
+                                } else {
+                                    String::new()
+                                };
+                                match series.fitted_n {
+                                    Some((n, true)) => {
+                                        messages.push(format!("{}Using n={}", prefix, n))
+                                    }
+                                    Some((n, false)) => messages.push(format!(
+                                        "{}Error target not reached, using n={} (error {:.6})",
+                                        prefix, n, series.approximation_error
+                                    )),
+                                    None => {}
+                                }
+                                if !series.warnings.is_empty() {
+                                    messages.push(format!("{}{}", prefix, series.warnings));
+                                }
+                            }
+                            if !messages.is_empty() {
+                                *status_msg = Some(messages.join("; "));
+                            }
+                            let mut calculated = calculated.into_iter();
+                            match (target, calculated.next()) {
+                                (SeriesTarget::Append, Some(series)) => {
+                                    animation_window.add_series(series.desc)
+                                }
+                                (SeriesTarget::Replace, Some(series)) => {
+                                    animation_window.is_open = true;
+                                    animation_window.set(Some(series.desc));
+                                    animation_window
+                                        .set_approximation_error(Some(series.approximation_error));
+                                    animation_window.set_target(Some(series.target));
+                                    animation_window.play();
+                                }
+                                (SeriesTarget::Playlist, Some(first)) => {
+                                    let entries = iter::once(first)
+                                        .chain(calculated)
+                                        .map(|series| PlaylistEntry {
+                                            name: series.name,
+                                            desc: series.desc,
+                                            approximation_error: Some(series.approximation_error),
+                                            target: Some(series.target),
+                                        })
+                                        .collect();
+                                    animation_window.is_open = true;
+                                    animation_window.set_playlist(entries);
+                                    animation_window.play();
+                                }
+                                (_, None) => {}
+                            }
+                        }
+                        Err(e) => *status_msg = Some(format!("Failed to calculate series: {}", e)),
+                    }
+                }
+                Err(TryRecvError::Empty) => {
+                    // Keep polling even if nothing else asks for a repaint
+                    ctx.request_repaint();
+                }
+                Err(TryRecvError::Disconnected) => {
+                    *pending_series = None;
+                    *status_msg = Some("Failed to calculate series: worker panicked".to_owned());
+                }
+            }
         }
 
+        // Series file to load this frame, either dropped or picked
+        let mut series_path = None;
         if let [file, ..] = &ctx.input().raw.dropped_files[..] {
             let path = file.path.as_ref();
-            if path
-                .map(|p| p.extension())
-                .flatten()
-                .map_or(false, |s| s == "svg")
+            let extension = path.and_then(|p| p.extension());
+            if let Some(path) =
+                path.filter(|_| extension.is_some_and(|s| s == "svg" || s == "csv" || s == "txt"))
             {
-                svg_select.disp_path = path.map(|p| p.display().to_string());
+                svg_select.select(path.display().to_string());
+            } else if extension.is_some_and(|s| s == "json") {
+                series_path = path.cloned();
             }
         }
 
@@ -261,9 +643,25 @@ impl epi::App for MyApp {
             );
         }
 
+        let mut transform = PlaneTransform::identity();
+        if *mirror_x {
+            transform = transform.then(PlaneTransform::mirror_x());
+        }
+        if *flip_y {
+            transform = transform.then(PlaneTransform::mirror_y());
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.label("This application helps you calculate fourier series functions from svgs.");
             svg_select.ui(ui);
+            ui.checkbox(&mut parse_options.arc_length, "Constant speed tracing")
+                .on_hover_text(
+                    "Map t by arc length instead of spending equal time on each segment",
+                );
+            ui.checkbox(&mut parse_options.normalize, "Normalize to unit box")
+                .on_hover_text("Center the curve and scale it to fit [-0.5, 0.5]");
+            ui.checkbox(&mut parse_options.connect_subpaths, "Connect subpaths")
+                .on_hover_text("Travel to each subpath along a straight line instead of jumping");
             ui.scope(|ui| {
                 // let should_btn_enable = svg_select.disp_path.is_some();
                 let btn_msg = "Preview SVG";
@@ -271,7 +669,25 @@ impl epi::App for MyApp {
                     if ui.button(btn_msg).clicked() {
                         svg_preview_window.reset();
                         svg_preview_window.is_open = true;
-                        svg_preview_window.set(parse_svg_into_proc(path));
+                        match parse_into_proc(path, parse_options) {
+                            Ok(loaded) => {
+                                if !loaded.warnings.is_empty() {
+                                    *status_msg = Some(loaded.warnings.to_string());
+                                }
+                                svg_preview_window.set(Some(loaded.proc));
+                                svg_preview_window.set_segments_count(loaded.segments_count);
+                                // Parsed again, as the path function does not keep them
+                                if !is_samples_path(path.as_ref()) {
+                                    let commands = parse_svg_commands_with(path, parse_options)
+                                        .map_or_else(|_| Vec::new(), |(commands, _)| commands);
+                                    svg_preview_window.set_commands(commands);
+                                }
+                            }
+                            Err(e) => {
+                                *status_msg = Some(format!("Failed to load {}: {}", path, e));
+                                svg_preview_window.set(None);
+                            }
+                        }
                         svg_preview_window.play();
                     }
                 } else {
@@ -282,12 +698,160 @@ impl epi::App for MyApp {
                 }
             });
 
+            if path_editor.ui(ui) {
+                match parse_path_data_into_proc_with(path_editor.data(), parse_options) {
+                    Ok((proc, segments_count, warnings)) => {
+                        path_editor.set_status(
+                            Some(warnings.to_string()).filter(|_| !warnings.is_empty()),
+                        );
+                        // The clock keeps running, so that edits show up in the moving preview
+                        svg_preview_window.is_open = true;
+                        svg_preview_window.set(Some(proc));
+                        svg_preview_window.set_segments_count(
+                            Some(segments_count).filter(|_| !parse_options.arc_length),
+                        );
+                        let commands = parse_path_data_with(path_editor.data(), parse_options)
+                            .map_or_else(|_| Vec::new(), |(commands, _)| commands);
+                        svg_preview_window.set_commands(commands);
+                        svg_preview_window.play();
+                    }
+                    Err(e) => path_editor.set_status(Some(e.to_string())),
+                }
+            }
+
+            ui.horizontal(|ui| {
+                let svg_path = svg_select
+                    .disp_path
+                    .as_ref()
+                    .filter(|p| !is_samples_path(p.as_ref()));
+                ui.set_enabled(svg_path.is_some());
+                ui.label("Samples:");
+                ui.add(egui::DragValue::new(export_samples_count).clamp_range(2..=100000));
+                if ui.button("Export samples as CSV").clicked() {
+                    // Suggested next to the SVG; the dialog asks before replacing a file
+                    let csv_path = svg_path.and_then(|path| {
+                        let path = std::path::Path::new(path);
+                        let mut dialog = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name(
+                                path.with_extension("csv").file_name()?.to_string_lossy(),
+                            );
+                        if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+                            dialog = dialog.set_directory(dir);
+                        }
+                        Some((path, dialog.save_file()?))
+                    });
+                    if let Some((path, csv_path)) = csv_path {
+                        let result = parse_svg_into_proc_with(path, parse_options)
+                            .map_err(|e| e.to_string())
+                            .and_then(|(proc, _, _)| {
+                                let func = |t| transform.apply(proc(t));
+                                util::samples::write_samples(&csv_path, func, *export_samples_count)
+                                    .map_err(|e| e.to_string())
+                            });
+                        *status_msg = Some(match result {
+                            Ok(()) => format!("Samples written to {}", csv_path.display()),
+                            Err(e) => format!("Failed to export samples: {}", e),
+                        });
+                    }
+                }
+            });
+            if ui.button("Load coefficients").clicked() {
+                series_path = rfd::FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .pick_file();
+            }
+            if let Some(msg) = status_msg {
+                ui.label(msg.as_str());
+            }
+
             ui.separator();
 
             ui.label("Note: n must be an odd number for series to be correctly calculated!");
-            let slider_n = egui::Slider::new(fourier_series_n, 9..=501).clamp_to_range(true);
+            // A confirmation only holds for the n and method it was given for
+            let method = |n: &usize, use_fft: &bool, sampling: &Sampling, o: &ConvertOptions| {
+                (*n, *use_fft, *sampling, o.quadrature, o.max_depth)
+            };
+            let confirmed_for = method(fourier_series_n, use_fft, sampling, convert_options);
+            let slider_n =
+                egui::Slider::new(fourier_series_n, config.series_n_range()).clamp_to_range(true);
             ui.add(slider_n);
+            ui.horizontal(|ui| {
+                ui.checkbox(fit_n, "Fit n automatically").on_hover_text(
+                    "Use the smallest n within the error, trying n up to the slider's value",
+                );
+                ui.set_enabled(*fit_n);
+                ui.label("Max error (RMS):");
+                ui.add(
+                    egui::DragValue::new(fit_max_error)
+                        .clamp_range(1e-6..=1.0)
+                        .speed(1e-4)
+                        .max_decimals(6),
+                );
+            });
+            ui.checkbox(use_fft, "Use FFT")
+                .on_hover_text("Much faster for large n, but less accurate around sharp features");
+            ui.horizontal(|ui| {
+                ui.set_enabled(*use_fft);
+                ui.label("Sampling:");
+                ui.selectable_value(sampling, Sampling::Uniform, "Uniform")
+                    .on_hover_text("Evenly spaced in t, as fast as FFT gets");
+                ui.selectable_value(sampling, Sampling::Chebyshev, "Chebyshev")
+                    .on_hover_text("Clustered around detected corners, reconstructing them better");
+            });
+            ui.horizontal(|ui| {
+                ui.set_enabled(!*use_fft);
+                ui.label("Max recursion depth:");
+                ui.add(egui::DragValue::new(&mut convert_options.max_depth).clamp_range(0..=24))
+                    .on_hover_text("Deeper subdivision resolves sharper features but takes longer");
+            });
+            ui.horizontal(|ui| {
+                ui.set_enabled(!*use_fft);
+                ui.label("Quadrature:");
+                for quadrature in Quadrature::ALL.iter().cloned() {
+                    let hover_text = match quadrature {
+                        Quadrature::Simpson => {
+                            "Can converge better than Gauss-Legendre on paths with sharp corners"
+                        }
+                        _ => "More points resolve smooth paths with fewer subdivisions",
+                    };
+                    ui.selectable_value(
+                        &mut convert_options.quadrature,
+                        quadrature,
+                        quadrature.name(),
+                    )
+                    .on_hover_text(hover_text);
+                }
+            });
 
+            if method(fourier_series_n, use_fft, sampling, convert_options) != confirmed_for {
+                *large_n_confirmed = false;
+            }
+            let n = *fourier_series_n;
+            let large_n_warning = match (*use_fft, *sampling) {
+                _ if n <= config.large_n_threshold => None,
+                (false, _) => Some(format!(
+                    "n = {} needs at least {} path evaluations and may take a while.",
+                    n,
+                    util::math::estimate_min_evaluations(n, convert_options.quadrature),
+                )),
+                (true, Sampling::Chebyshev) => Some(format!(
+                    "n = {} sums about {} terms and may take a while.",
+                    n,
+                    util::math::estimate_chebyshev_terms(n),
+                )),
+                // A single FFT stays fast for any n on the slider
+                (true, Sampling::Uniform) => None,
+            };
+            let large_n = large_n_warning.is_some();
+            if let Some(warning) = large_n_warning {
+                ui.colored_label(egui::Color32::YELLOW, warning);
+                ui.checkbox(large_n_confirmed, "Compute anyway");
+            }
+
+            // Files to calculate the series of, and what to do with them
+            let mut calculation = None;
+            let allowed = (!large_n || *large_n_confirmed) && pending_series.is_none();
             ui.scope(|ui| {
                 // ui.set_enabled(svg_select.disp_path.is_some());
                 // if ui.button("Calculate & Show").clicked() {
@@ -295,21 +859,30 @@ impl epi::App for MyApp {
                 // }
 
                 let btn_msg = "Calculate & Show";
-                if let Some(path) = &svg_select.disp_path {
-                    if ui.button(btn_msg).clicked() {
-                        animation_window.reset();
-                        animation_window.is_open = true;
-
-                        if *fourier_series_n % 2 == 0 {
-                            *fourier_series_n += 1;
-                        }
-
-                        let desc = parse_svg_into_proc(path).map(|proc| {
-                            util::math::convert_to_fourier_series(proc, *fourier_series_n)
-                        });
-                        // dbg!(&desc);
-                        animation_window.set(desc);
-                        animation_window.play();
+                if let Some(path) = svg_select.disp_path.as_ref().filter(|_| allowed) {
+                    let (calculate, append) = ui
+                        .horizontal(|ui| {
+                            let calculate = ui.button(btn_msg).clicked();
+                            let append = ui
+                                .scope(|ui| {
+                                    ui.set_enabled(animation_window.has_series());
+                                    ui.button("Add to animation")
+                                        .on_hover_text(
+                                            "Draw this file along with the current animation",
+                                        )
+                                        .clicked()
+                                })
+                                .inner;
+                            (calculate, append)
+                        })
+                        .inner;
+                    if calculate || append {
+                        let target = if calculate {
+                            SeriesTarget::Replace
+                        } else {
+                            SeriesTarget::Append
+                        };
+                        calculation = Some((vec![path.clone()], target));
                     }
                 } else {
                     ui.set_enabled(false);
@@ -319,9 +892,76 @@ impl epi::App for MyApp {
                 }
             });
 
+            if playlist.ui(ui, svg_select.disp_path.as_ref(), allowed) {
+                calculation = Some((playlist.paths().to_vec(), SeriesTarget::Playlist));
+            }
+
+            if let Some((paths, target)) = calculation {
+                if target != SeriesTarget::Append {
+                    animation_window.reset();
+                }
+                if *fourier_series_n % 2 == 0 {
+                    *fourier_series_n += 1;
+                }
+                let series_n = if *fit_n {
+                    SeriesN::Fit {
+                        max_n: *fourier_series_n,
+                        max_error: *fit_max_error,
+                    }
+                } else {
+                    SeriesN::Fixed(*fourier_series_n)
+                };
+                *pending_series = Some(spawn_calculation(
+                    paths,
+                    series_n,
+                    if *use_fft { Some(*sampling) } else { None },
+                    parse_options.clone(),
+                    convert_options.clone(),
+                    target,
+                    config,
+                ));
+            }
+
+            if let Some(pending) = pending_series {
+                // The FFT path does not report progress, but finishes quickly
+                let done = pending.progress.load(Ordering::Relaxed);
+                let n = pending.n.load(Ordering::Relaxed).max(1);
+                let mut text = format!("Calculated {}/{} coefficients", done, n);
+                if pending.files_count > 1 {
+                    let file = pending.file.load(Ordering::Relaxed);
+                    text += &format!(" of file {}/{}", file + 1, pending.files_count);
+                }
+                let progress_bar = egui::ProgressBar::new(done as f32 / n as f32)
+                    .text(text)
+                    .animate(true);
+                ui.add(progress_bar);
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("App theme:");
+                let was_dark = *dark_mode;
+                ui.selectable_value(dark_mode, false, "☀ Light");
+                ui.selectable_value(dark_mode, true, "🌙 Dark");
+                if *dark_mode != was_dark {
+                    ctx.set_visuals(app_visuals(*dark_mode));
+                }
+            });
+            ui.add(
+                egui::Slider::new(ui_scale, UI_SCALE_RANGE)
+                    .clamp_to_range(true)
+                    .text("UI scale"),
+            );
+            plot_theme.ui(ui);
+            ui.checkbox(mirror_x, "Mirror horizontally");
+            ui.checkbox(flip_y, "Flip vertically")
+                .on_hover_text("SVG y points downwards; flip to show shapes as SVG viewers do");
+
             ui.separator();
 
             frame_history.ui(ui);
+            ui.checkbox(show_window_timings, "Show window timings");
 
             ui.separator();
 
@@ -335,8 +975,33 @@ impl epi::App for MyApp {
             });
         });
 
-        let mut drawn = animation_window.show(ctx) && animation_window.is_playing();
-        drawn = (svg_preview_window.show(ctx) && svg_preview_window.is_playing()) || drawn;
+        if let Some(path) = series_path {
+            match load_series_json(&path) {
+                Ok(desc) => {
+                    *status_msg = Some(format!(
+                        "Loaded {} coefficients from {}",
+                        desc.as_vec().len(),
+                        path.display()
+                    ));
+                    animation_window.reset();
+                    animation_window.is_open = true;
+                    animation_window.set(Some(desc));
+                    animation_window.play();
+                }
+                Err(e) => *status_msg = Some(format!("Failed to load coefficients: {}", e)),
+            }
+        }
+
+        animation_window.set_plot_theme(*plot_theme);
+        svg_preview_window.set_plot_theme(*plot_theme);
+        animation_window.set_transform(transform);
+        svg_preview_window.set_transform(transform);
+
+        let mut drawn =
+            animation_window.show(ctx, *show_window_timings) && animation_window.is_playing();
+        drawn = (svg_preview_window.show(ctx, *show_window_timings)
+            && svg_preview_window.is_playing())
+            || drawn;
 
         if drawn {
             ctx.request_repaint();
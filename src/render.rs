@@ -0,0 +1,2 @@
+pub mod raster;
+pub mod vector;
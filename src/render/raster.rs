@@ -0,0 +1,222 @@
+use crate::util::{math::FourierSeriesDesc, transform::PlaneTransform};
+use image::{Rgba, RgbaImage};
+use num::Complex;
+
+pub struct RasterSettings {
+    pub width: u32,
+    pub height: u32,
+    pub background: Rgba<u8>,
+    pub trace_color: Rgba<u8>,
+    pub arrow_color: Rgba<u8>,
+    pub show_arrows: bool,
+    // Number of points sampled along the trace
+    pub trace_samples: usize,
+    // Fraction of the image left empty around the full curve
+    pub margin: f64,
+    pub transform: PlaneTransform,
+}
+
+impl Default for RasterSettings {
+    fn default() -> Self {
+        Self {
+            width: 512,
+            height: 512,
+            background: Rgba([255, 255, 255, 255]),
+            trace_color: Rgba([200, 40, 40, 255]),
+            arrow_color: Rgba([60, 60, 60, 255]),
+            show_arrows: true,
+            trace_samples: 1000,
+            margin: 0.05,
+            transform: Default::default(),
+        }
+    }
+}
+
+// Maps plot coordinates to pixel coordinates, keeping a 1:1 data aspect like the plots do
+struct Viewport {
+    center: Complex<f64>,
+    scale: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Viewport {
+    fn fit(points: &[Complex<f64>], settings: &RasterSettings) -> Self {
+        let (width, height) = (settings.width as f64, settings.height as f64);
+        let (mut min, mut max) = (
+            Complex::new(f64::INFINITY, f64::INFINITY),
+            Complex::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        );
+        for p in points {
+            min = Complex::new(min.re.min(p.re), min.im.min(p.im));
+            max = Complex::new(max.re.max(p.re), max.im.max(p.im));
+        }
+        let size = max - min;
+        let usable = 1.0 - 2.0 * settings.margin;
+        let scale = (width * usable / size.re).min(height * usable / size.im);
+        Self {
+            center: (min + max) / 2.0,
+            scale: if scale.is_finite() { scale } else { 1.0 },
+            width,
+            height,
+        }
+    }
+
+    fn to_pixel(&self, p: Complex<f64>) -> (f64, f64) {
+        let d = (p - self.center) * self.scale;
+        // Pixel rows grow downwards
+        (self.width / 2.0 + d.re, self.height / 2.0 - d.im)
+    }
+}
+
+fn blend_pixel(image: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>) {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+        return;
+    }
+    let alpha = color.0[3] as u32;
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for c in 0..3 {
+        pixel.0[c] = ((color.0[c] as u32 * alpha + pixel.0[c] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    pixel.0[3] = pixel.0[3].max(color.0[3]);
+}
+
+fn draw_line(image: &mut RgbaImage, from: (f64, f64), to: (f64, f64), color: Rgba<u8>) {
+    let steps = (to.0 - from.0)
+        .abs()
+        .max((to.1 - from.1).abs())
+        .ceil()
+        .max(1.0) as usize;
+    let mut last = None;
+    for i in 0..=steps {
+        let prog = i as f64 / steps as f64;
+        let x = (from.0 + (to.0 - from.0) * prog).round() as i64;
+        let y = (from.1 + (to.1 - from.1) * prog).round() as i64;
+        // Avoid blending the same pixel twice
+        if last != Some((x, y)) {
+            blend_pixel(image, x, y, color);
+            last = Some((x, y));
+        }
+    }
+}
+
+fn draw_arrow(image: &mut RgbaImage, origin: (f64, f64), tip: (f64, f64), color: Rgba<u8>) {
+    draw_line(image, origin, tip, color);
+
+    // Same head proportions as egui's plot arrows
+    let back = Complex::new(origin.0 - tip.0, origin.1 - tip.1) / 4.0;
+    for angle in [-std::f64::consts::FRAC_PI_6, std::f64::consts::FRAC_PI_6] {
+        let head = back * Complex::from_polar(1.0, angle);
+        draw_line(image, tip, (tip.0 + head.re, tip.1 + head.im), color);
+    }
+}
+
+// Renders the trace from 0 to t along with the epicycle arrows at t
+// The view is fitted to the full curve so consecutive frames line up
+pub fn render_frame(desc: &FourierSeriesDesc<f64>, t: f64, settings: &RasterSettings) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(settings.width, settings.height, settings.background);
+    let series_fn = desc.as_fn();
+    let func = |t| settings.transform.apply(series_fn(t));
+    let samples = settings.trace_samples.max(1);
+
+    let full_curve: Vec<_> = (0..=samples)
+        .map(|i| func(i as f64 / samples as f64))
+        .collect();
+    let viewport = Viewport::fit(&full_curve, settings);
+
+    let trace: Vec<_> = (0..=samples)
+        .map(|i| viewport.to_pixel(func(i as f64 / samples as f64 * t)))
+        .collect();
+    for pair in trace.windows(2) {
+        draw_line(&mut image, pair[0], pair[1], settings.trace_color);
+    }
+
+    if settings.show_arrows {
+        let mut origin = viewport.to_pixel(Complex::new(0.0, 0.0));
+        for tip in desc.epicycle_points(t) {
+            let tip = viewport.to_pixel(settings.transform.apply(tip));
+            draw_arrow(&mut image, origin, tip, settings.arrow_color);
+            origin = tip;
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(center: Complex<f64>, radius: f64) -> FourierSeriesDesc<f64> {
+        FourierSeriesDesc::from_coefficients(vec![
+            Complex::new(0.0, 0.0),
+            center,
+            Complex::new(radius, 0.0),
+        ])
+    }
+
+    // Return value: (min x, min y, max x, max y) of the pixels that differ from the background
+    fn painted_bounds(image: &RgbaImage, background: Rgba<u8>) -> Option<(u32, u32, u32, u32)> {
+        image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| **pixel != background)
+            .fold(None, |bounds, (x, y, _)| {
+                let (x0, y0, x1, y1) = bounds.unwrap_or((x, y, x, y));
+                Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)))
+            })
+    }
+
+    fn assert_bounds(actual: (u32, u32, u32, u32), expected: (f64, f64, f64, f64)) {
+        let actual = [actual.0, actual.1, actual.2, actual.3];
+        let expected = [expected.0, expected.1, expected.2, expected.3];
+        for (&a, &e) in actual.iter().zip(&expected) {
+            assert!(
+                (a as f64 - e).abs() <= 1.0,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn trace_fills_the_image_within_the_margin() {
+        let settings = RasterSettings {
+            width: 400,
+            height: 200,
+            show_arrows: false,
+            ..Default::default()
+        };
+        let image = render_frame(&circle(Complex::new(3.0, -2.0), 5.0), 1.0, &settings);
+        assert_eq!(image.dimensions(), (400, 200));
+        // The height limits the scale; the circle is centered horizontally
+        let bounds = painted_bounds(&image, settings.background).unwrap();
+        assert_bounds(bounds, (110.0, 10.0, 290.0, 190.0));
+    }
+
+    #[test]
+    fn arrows_leaving_the_image_are_clipped() {
+        // The first arrow starts at the origin, far outside the fitted view
+        let settings = RasterSettings {
+            width: 100,
+            height: 100,
+            margin: 0.1,
+            ..Default::default()
+        };
+        let desc = circle(Complex::new(1000.0, 1000.0), 10.0);
+        let image = render_frame(&desc, 0.25, &settings);
+        assert_eq!(image.dimensions(), (100, 100));
+        // It enters at the bottom left, where the origin lies in pixel coordinates
+        let bounds = painted_bounds(&image, settings.background).unwrap();
+        assert_eq!((bounds.0, bounds.3), (0, 99));
+
+        // The trace alone stays within the margin
+        let settings = RasterSettings {
+            show_arrows: false,
+            ..settings
+        };
+        let image = render_frame(&desc, 1.0, &settings);
+        let bounds = painted_bounds(&image, settings.background).unwrap();
+        assert_bounds(bounds, (10.0, 10.0, 90.0, 90.0));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::util::{math::FourierSeriesDesc, transform::PlaneTransform};
+use num::Complex;
+use std::{io, path::Path};
+use svg::{
+    node::element::{self, path::Data},
+    Document,
+};
+
+// Traces the whole series as a closed polyline through `samples` evenly spaced points, in the
+// same coordinates as the source path so that the result can be parsed again
+pub fn series_to_svg(
+    desc: &FourierSeriesDesc<f64>,
+    samples: usize,
+    transform: &PlaneTransform,
+) -> Document {
+    let series_fn = desc.as_fn();
+    let points: Vec<_> = (0..samples.max(1))
+        .map(|i| transform.apply(series_fn(i as f64 / samples as f64)))
+        .collect();
+
+    let (mut min, mut max) = (
+        Complex::new(f64::INFINITY, f64::INFINITY),
+        Complex::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+    );
+    for p in &points {
+        min = Complex::new(min.re.min(p.re), min.im.min(p.im));
+        max = Complex::new(max.re.max(p.re), max.im.max(p.im));
+    }
+    // Keep the view box valid for degenerate curves such as a single point
+    let size = Complex::new((max.re - min.re).max(1e-6), (max.im - min.im).max(1e-6));
+
+    let mut data = Data::new().move_to((points[0].re, points[0].im));
+    for p in &points[1..] {
+        data = data.line_to((p.re, p.im));
+    }
+    let path = element::Path::new()
+        .set("fill", "none")
+        .set("stroke", "black")
+        .set("stroke-width", size.re.max(size.im) / 500.0)
+        .set("d", data.close());
+
+    Document::new()
+        .set("viewBox", (min.re, min.im, size.re, size.im))
+        .add(path)
+}
+
+pub fn write_svg<P: AsRef<Path>>(
+    path: P,
+    desc: &FourierSeriesDesc<f64>,
+    samples: usize,
+    transform: &PlaneTransform,
+) -> io::Result<()> {
+    svg::save(path, &series_to_svg(desc, samples, transform))
+}
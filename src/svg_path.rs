@@ -0,0 +1,1009 @@
+pub mod transform;
+
+use std::collections::BTreeSet;
+
+use num::complex::Complex;
+use svg::node::{
+    element::path::{Command, Position},
+    Attributes,
+};
+use transform::AffineTransform;
+
+// Shareable across threads so that coefficients can be computed in parallel
+pub type PathFnBox = Box<dyn Fn(f64) -> Complex<f64> + Send + Sync>;
+
+pub fn cubic_bezier(
+    p0: Complex<f64>,
+    p1: Complex<f64>,
+    p2: Complex<f64>,
+    p3: Complex<f64>,
+    t: f64,
+) -> Complex<f64> {
+    let inv_t = 1.0 - t;
+    inv_t.powi(3) * p0
+        + 3.0 * inv_t.powi(2) * t * p1
+        + 3.0 * inv_t * t.powi(2) * p2
+        + t.powi(3) * p3
+}
+
+// Absolute drawing commands that every supported SVG element is converted into, each
+// starting where the previous one ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmdData {
+    Move(Complex<f64>),
+    Line(Complex<f64>),
+    CubicCurve(Complex<f64>, Complex<f64>, Complex<f64>),
+}
+
+// Path data syntax, e.g. "C 1 2, 3 4, 5 6"
+impl std::fmt::Display for CmdData {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CmdData::Move(p) => write!(f, "M {} {}", p.re, p.im),
+            CmdData::Line(p) => write!(f, "L {} {}", p.re, p.im),
+            CmdData::CubicCurve(p1, p2, p3) => write!(
+                f,
+                "C {} {}, {} {}, {} {}",
+                p1.re, p1.im, p2.re, p2.im, p3.re, p3.im
+            ),
+        }
+    }
+}
+
+impl CmdData {
+    // Point at prog in [0, 1] along the segment starting at start
+    fn point_at(&self, start: Complex<f64>, prog: f64) -> Complex<f64> {
+        match self {
+            CmdData::Move(p0) => *p0,
+            CmdData::Line(p1) => start + (*p1 - start) * prog,
+            CmdData::CubicCurve(p1, p2, p3) => cubic_bezier(start, *p1, *p2, *p3, prog),
+        }
+    }
+
+    fn end_point(&self) -> Complex<f64> {
+        match self {
+            CmdData::Move(p) | CmdData::Line(p) | CmdData::CubicCurve(_, _, p) => *p,
+        }
+    }
+
+    // Affine maps keep lines straight and map Bezier curves through their control points
+    fn transformed(self, transform: &AffineTransform) -> Self {
+        match self {
+            CmdData::Move(p0) => CmdData::Move(transform.apply(p0)),
+            CmdData::Line(p1) => CmdData::Line(transform.apply(p1)),
+            CmdData::CubicCurve(p1, p2, p3) => CmdData::CubicCurve(
+                transform.apply(p1),
+                transform.apply(p2),
+                transform.apply(p3),
+            ),
+        }
+    }
+}
+
+// Approximates an elliptical arc with cubic curves of at most 90 degrees each, following the
+// endpoint to center parameterization of the SVG spec (F.6.5)
+pub fn arc_to_cubics(
+    p0: Complex<f64>,
+    radii: Complex<f64>,
+    x_axis_rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    p1: Complex<f64>,
+) -> Vec<CmdData> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    if p0 == p1 {
+        return vec![];
+    }
+    let (mut rx, mut ry) = (radii.re.abs(), radii.im.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![CmdData::Line(p1)];
+    }
+
+    let rotation = Complex::from_polar(1.0, x_axis_rotation.to_radians());
+    let p0_prime = (p0 - p1) / 2.0 / rotation;
+    let (x1, y1) = (p0_prime.re, p0_prime.im);
+
+    // Scale up radii that are too small to span the endpoints
+    let lambda = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+    let den = (rx * y1).powi(2) + (ry * x1).powi(2);
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coef = sign * (num / den).max(0.0).sqrt();
+    let center_prime = Complex::new(coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let center = center_prime * rotation + (p0 + p1) / 2.0;
+
+    let theta1 = Complex::new((x1 - center_prime.re) / rx, (y1 - center_prime.im) / ry).arg();
+    let theta2 = Complex::new((-x1 - center_prime.re) / rx, (-y1 - center_prime.im) / ry).arg();
+    let mut delta = theta2 - theta1;
+    if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    } else if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    }
+
+    // Maps a point on the unit circle onto the ellipse
+    let map = |u: Complex<f64>| center + Complex::new(rx * u.re, ry * u.im) * rotation;
+
+    let count = (delta.abs() / FRAC_PI_2 - 1e-9).ceil().max(1.0) as usize;
+    let step = delta / count as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    (0..count)
+        .map(|i| {
+            let a = theta1 + step * i as f64;
+            let b = a + step;
+            let (ua, ub) = (Complex::from_polar(1.0, a), Complex::from_polar(1.0, b));
+            let c1 = map(ua + ua * Complex::i() * k);
+            let c2 = map(ub - ub * Complex::i() * k);
+            let end = if i + 1 == count { p1 } else { map(ub) };
+            CmdData::CubicCurve(c1, c2, end)
+        })
+        .collect()
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SvgParseError {
+    #[error("Failed to read SVG file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("SVG is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    #[error("SVG is malformed: {0}")]
+    Malformed(svg::parser::Error),
+    #[error("Found a path without `d` attribute")]
+    MissingData,
+    #[error("Found a polygon or polyline without `points` attribute")]
+    MissingPoints,
+    #[error("Path data is invalid: {0}")]
+    InvalidPathData(svg::parser::Error),
+    #[error("Found unrecognized command `{0}`")]
+    UnrecognizedCommand(String),
+    #[error("No supported path found, SVG uses unsupported commands: {0}")]
+    OnlyUnsupportedCommands(String),
+    #[error("Parameters is invalid")]
+    InvalidParameter,
+    #[error("Transform is invalid: {0}")]
+    InvalidTransform(String),
+    #[error("No path found in SVG")]
+    NoPathFound,
+}
+
+// Converts path commands into absolute CmdData, tracking the current point so that
+// relative commands can be resolved
+#[derive(Default)]
+pub struct CmdDataConverter {
+    cur_pos: Complex<f64>,
+    // Start of the current subpath, where a close command returns to
+    subpath_start: Complex<f64>,
+    // Whether a command has set the current point, which a valid path does with a move
+    started: bool,
+    // Whether the path started with another command, which the SVG spec does not allow
+    missing_move: bool,
+}
+
+impl CmdDataConverter {
+    fn point(&self, position: Position, x: f32, y: f32) -> Complex<f64> {
+        let p = Complex::new(x.into(), y.into());
+        match position {
+            Position::Absolute => p,
+            Position::Relative => self.cur_pos + p,
+        }
+    }
+
+    // Return value: where a path starting with command without a move should start, None for
+    // moves, absolute lines and unsupported commands
+    fn implicit_start(command: &Command) -> Option<Complex<f64>> {
+        match command {
+            Command::CubicCurve(Position::Absolute, param)
+            | Command::QuadraticCurve(Position::Absolute, param) => Some(Complex::new(
+                (*param.first()?).into(),
+                (*param.get(1)?).into(),
+            )),
+            // Relative points are relative to the origin then, while arcs and horizontal and
+            // vertical lines name no whole point but their end, so these start at the origin
+            Command::Line(Position::Relative, _)
+            | Command::HorizontalLine(..)
+            | Command::VerticalLine(..)
+            | Command::CubicCurve(..)
+            | Command::QuadraticCurve(..)
+            | Command::EllipticalArc(..) => Some(Complex::new(0.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    // Whether the path started without a move, making convert guess its start
+    pub fn missing_move(&self) -> bool {
+        self.missing_move
+    }
+
+    pub fn convert(&mut self, command: &Command) -> Result<Vec<CmdData>, SvgParseError> {
+        if !self.started {
+            if let Command::Line(Position::Absolute, param) = command {
+                // Its first point becomes the start, followed by lines as after a move
+                self.missing_move = true;
+                return self.convert(&Command::Move(Position::Absolute, param.clone()));
+            }
+            if let Some(start) = Self::implicit_start(command) {
+                // Read as if preceded by a move to start
+                self.missing_move = true;
+                self.started = true;
+                self.cur_pos = start;
+                self.subpath_start = start;
+                let mut result = vec![CmdData::Move(start)];
+                result.extend(self.convert(command)?);
+                return Ok(result);
+            }
+        }
+
+        let result = match command {
+            Command::Move(position, param) => {
+                if param.is_empty() || param.len() % 2 != 0 {
+                    return Err(SvgParseError::InvalidParameter);
+                }
+
+                self.started = true;
+                // Pairs after the first are implicit line commands
+                let mut vec_result = Vec::new();
+                for (i, s) in param.chunks_exact(2).enumerate() {
+                    let p = self.point(*position, s[0], s[1]);
+                    self.cur_pos = p;
+                    vec_result.push(if i == 0 {
+                        self.subpath_start = p;
+                        CmdData::Move(p)
+                    } else {
+                        CmdData::Line(p)
+                    });
+                }
+
+                vec_result
+            }
+            Command::Line(position, param) => {
+                if param.len() % 2 != 0 {
+                    return Err(SvgParseError::InvalidParameter);
+                }
+
+                let mut vec_result = Vec::new();
+                for s in param.chunks_exact(2) {
+                    let p = self.point(*position, s[0], s[1]);
+                    self.cur_pos = p;
+                    vec_result.push(CmdData::Line(p));
+                }
+
+                vec_result
+            }
+            Command::HorizontalLine(position, param) => param
+                .iter()
+                .map(|&x| {
+                    let x = f64::from(x);
+                    self.cur_pos.re = match position {
+                        Position::Absolute => x,
+                        Position::Relative => self.cur_pos.re + x,
+                    };
+                    CmdData::Line(self.cur_pos)
+                })
+                .collect(),
+            Command::VerticalLine(position, param) => param
+                .iter()
+                .map(|&y| {
+                    let y = f64::from(y);
+                    self.cur_pos.im = match position {
+                        Position::Absolute => y,
+                        Position::Relative => self.cur_pos.im + y,
+                    };
+                    CmdData::Line(self.cur_pos)
+                })
+                .collect(),
+            Command::CubicCurve(position, param) => {
+                if param.len() % 6 != 0 {
+                    return Err(SvgParseError::InvalidParameter);
+                }
+
+                let mut vec_result = Vec::new();
+                for s in param.chunks_exact(6) {
+                    // All points of a relative curve are relative to its start
+                    let p1 = self.point(*position, s[0], s[1]);
+                    let p2 = self.point(*position, s[2], s[3]);
+                    let p3 = self.point(*position, s[4], s[5]);
+                    self.cur_pos = p3;
+                    vec_result.push(CmdData::CubicCurve(p1, p2, p3));
+                }
+
+                vec_result
+            }
+            Command::QuadraticCurve(position, param) => {
+                if param.len() % 4 != 0 {
+                    return Err(SvgParseError::InvalidParameter);
+                }
+
+                let mut vec_result = Vec::new();
+                for s in param.chunks_exact(4) {
+                    let p0 = self.cur_pos;
+                    let q1 = self.point(*position, s[0], s[1]);
+                    let p3 = self.point(*position, s[2], s[3]);
+                    // Degree elevation into an equivalent cubic curve
+                    let p1 = p0 + (q1 - p0) * (2.0 / 3.0);
+                    let p2 = p3 + (q1 - p3) * (2.0 / 3.0);
+                    self.cur_pos = p3;
+                    vec_result.push(CmdData::CubicCurve(p1, p2, p3));
+                }
+
+                vec_result
+            }
+            Command::EllipticalArc(position, param) => {
+                if param.len() % 7 != 0 {
+                    return Err(SvgParseError::InvalidParameter);
+                }
+
+                let mut vec_result = Vec::new();
+                for s in param.chunks_exact(7) {
+                    let p0 = self.cur_pos;
+                    let p1 = self.point(*position, s[5], s[6]);
+                    let radii = Complex::new(s[0].into(), s[1].into());
+                    vec_result.append(&mut arc_to_cubics(
+                        p0,
+                        radii,
+                        s[2].into(),
+                        s[3] != 0.0,
+                        s[4] != 0.0,
+                        p1,
+                    ));
+                    self.cur_pos = p1;
+                }
+
+                vec_result
+            }
+            Command::Close => {
+                let start = self.subpath_start;
+                if self.cur_pos == start {
+                    // Already closed, avoid an empty segment
+                    vec![]
+                } else {
+                    self.cur_pos = start;
+                    vec![CmdData::Line(start)]
+                }
+            }
+            other_cmd => {
+                return Err(SvgParseError::UnrecognizedCommand(
+                    command_letter(other_cmd).to_owned(),
+                ))
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+// Return value: the upper case letter of a path command, as written in `d`
+fn command_letter(command: &Command) -> &'static str {
+    match command {
+        Command::Move(..) => "M",
+        Command::Line(..) => "L",
+        Command::HorizontalLine(..) => "H",
+        Command::VerticalLine(..) => "V",
+        Command::CubicCurve(..) => "C",
+        Command::SmoothCubicCurve(..) => "S",
+        Command::QuadraticCurve(..) => "Q",
+        Command::SmoothQuadraticCurve(..) => "T",
+        Command::EllipticalArc(..) => "A",
+        Command::Close => "Z",
+    }
+}
+
+// Parts of an SVG that were left out of the traced path
+#[derive(Debug, Clone, Default)]
+pub struct ParseWarnings {
+    // Letters of the path commands that could not be converted
+    pub unsupported_commands: BTreeSet<String>,
+    pub text_skipped: bool,
+    // A path started without a move, so its start was guessed
+    pub missing_move: bool,
+    // A shape was sized relative to the viewport or font, e.g. a 100% background
+    pub relative_shape_skipped: bool,
+}
+
+impl ParseWarnings {
+    pub fn is_empty(&self) -> bool {
+        self.unsupported_commands.is_empty()
+            && !self.text_skipped
+            && !self.missing_move
+            && !self.relative_shape_skipped
+    }
+}
+
+impl std::fmt::Display for ParseWarnings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut messages = Vec::new();
+        if !self.unsupported_commands.is_empty() {
+            let commands = self.unsupported_commands.iter().cloned();
+            messages.push(format!(
+                "This SVG uses unsupported commands: {}",
+                commands.collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if self.text_skipped {
+            messages.push("Text elements were skipped as no font is loaded".to_owned());
+        }
+        if self.missing_move {
+            messages.push(
+                "A path does not start with a move, so its start was guessed from its first command"
+                    .to_owned(),
+            );
+        }
+        if self.relative_shape_skipped {
+            messages.push("Shapes sized relative to the viewport or font were skipped".to_owned());
+        }
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+// Parses the `points` attribute of polygon and polyline elements, a list of numbers separated
+// by whitespace and/or commas. A trailing odd number is ignored like browsers do.
+fn parse_points(points: &str) -> Result<Vec<Complex<f64>>, SvgParseError> {
+    let numbers = points
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| SvgParseError::InvalidParameter)?;
+    Ok(numbers
+        .chunks_exact(2)
+        .map(|s| Complex::new(s[0], s[1]))
+        .collect())
+}
+
+// Return value: a Move to the first point followed by lines through the rest, returning to
+// the first point if closed
+fn polyline_commands(points: &[Complex<f64>], closed: bool) -> Vec<CmdData> {
+    let (first, rest) = match points.split_first() {
+        Some(split) => split,
+        None => return vec![],
+    };
+    let mut cmd_vec = vec![CmdData::Move(*first)];
+    cmd_vec.extend(rest.iter().map(|p| CmdData::Line(*p)));
+    if closed && points.last() != Some(first) {
+        cmd_vec.push(CmdData::Line(*first));
+    }
+    cmd_vec
+}
+
+// Why a length attribute could not be given in user units
+enum LengthError {
+    // Percentages and font-relative units depend on a viewport or font that is not known
+    Relative,
+    Invalid,
+}
+
+// Return value: value of a length attribute in user units, 0 if absent
+fn length_attribute(attributes: &Attributes, name: &str) -> Result<f64, LengthError> {
+    let value = match attributes.get(name) {
+        Some(value) => value.trim(),
+        None => return Ok(0.0),
+    };
+    let unit_start = value
+        .rfind(|c: char| c.is_ascii_digit() || c == '.')
+        .map_or(0, |i| i + 1);
+    let (number, unit) = value.split_at(unit_start);
+    // Absolute units at the CSS resolution of 96 user units per inch
+    let scale = match unit {
+        "" | "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "Q" => 96.0 / 101.6,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "%" | "em" | "ex" | "ch" | "rem" | "vw" | "vh" | "vmin" | "vmax" => {
+            return Err(LengthError::Relative)
+        }
+        _ => return Err(LengthError::Invalid),
+    };
+    number
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| LengthError::Invalid)
+}
+
+// Return value: outline of a circle or ellipse element, empty if it has no area
+fn ellipse_element(attributes: &Attributes, is_circle: bool) -> Result<Vec<CmdData>, LengthError> {
+    let center = Complex::new(
+        length_attribute(attributes, "cx")?,
+        length_attribute(attributes, "cy")?,
+    );
+    let radii = if is_circle {
+        let r = length_attribute(attributes, "r")?;
+        Complex::new(r, r)
+    } else {
+        Complex::new(
+            length_attribute(attributes, "rx")?,
+            length_attribute(attributes, "ry")?,
+        )
+    };
+    // Shapes without area are not rendered
+    if radii.re > 0.0 && radii.im > 0.0 {
+        Ok(ellipse_commands(center, radii))
+    } else {
+        Ok(vec![])
+    }
+}
+
+// Return value: outline of a rect element, empty if it has no area
+fn rect_element(attributes: &Attributes) -> Result<Vec<CmdData>, LengthError> {
+    let corner = Complex::new(
+        length_attribute(attributes, "x")?,
+        length_attribute(attributes, "y")?,
+    );
+    let size = Complex::new(
+        length_attribute(attributes, "width")?,
+        length_attribute(attributes, "height")?,
+    );
+    // A missing radius takes the value of the other one
+    let (rx, ry) = match (attributes.get("rx"), attributes.get("ry")) {
+        (Some(_), None) => {
+            let rx = length_attribute(attributes, "rx")?;
+            (rx, rx)
+        }
+        (None, Some(_)) => {
+            let ry = length_attribute(attributes, "ry")?;
+            (ry, ry)
+        }
+        _ => (
+            length_attribute(attributes, "rx")?,
+            length_attribute(attributes, "ry")?,
+        ),
+    };
+    if size.re > 0.0 && size.im > 0.0 {
+        let radii = Complex::new(rx.clamp(0.0, size.re / 2.0), ry.clamp(0.0, size.im / 2.0));
+        Ok(rect_commands(corner, size, radii))
+    } else {
+        Ok(vec![])
+    }
+}
+
+// Return value: four quarter arcs around the center, starting on its right like browsers do
+fn ellipse_commands(center: Complex<f64>, radii: Complex<f64>) -> Vec<CmdData> {
+    let start = center + radii.re;
+    let opposite = center - radii.re;
+    let mut cmd_vec = vec![CmdData::Move(start)];
+    cmd_vec.append(&mut arc_to_cubics(start, radii, 0.0, false, true, opposite));
+    cmd_vec.append(&mut arc_to_cubics(opposite, radii, 0.0, false, true, start));
+    cmd_vec
+}
+
+// Return value: outline of a rectangle, with corners rounded by radii if they are nonzero
+fn rect_commands(corner: Complex<f64>, size: Complex<f64>, radii: Complex<f64>) -> Vec<CmdData> {
+    let (rx, ry) = (Complex::new(radii.re, 0.0), Complex::new(0.0, radii.im));
+    let (w, h) = (Complex::new(size.re, 0.0), Complex::new(0.0, size.im));
+    let mut cmd_vec = vec![CmdData::Move(corner + rx)];
+    // Goes along each side and then around the corner following it
+    let sides = [
+        (corner + w - rx, corner + w + ry),
+        (corner + w + h - ry, corner + w + h - rx),
+        (corner + h + rx, corner + h - ry),
+        (corner + ry, corner + rx),
+    ];
+    let mut end = corner + rx;
+    for &(side_end, corner_end) in &sides {
+        // Sides vanish when the radii take up all of them
+        if side_end != end {
+            cmd_vec.push(CmdData::Line(side_end));
+        }
+        cmd_vec.append(&mut arc_to_cubics(
+            side_end, radii, 0.0, false, true, corner_end,
+        ));
+        end = corner_end;
+    }
+    cmd_vec
+}
+
+// Return value: transform of an element, including the one inherited from its parent
+fn element_transform(
+    parent: &AffineTransform,
+    attributes: &Attributes,
+) -> Result<AffineTransform, SvgParseError> {
+    match attributes.get("transform") {
+        Some(value) => {
+            let own = AffineTransform::parse(value).map_err(SvgParseError::InvalidTransform)?;
+            Ok(parent.compose(&own))
+        }
+        None => Ok(*parent),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    // Map t by arc length so that the pen moves at roughly constant speed, instead of
+    // spending equal time on every segment
+    pub arc_length: bool,
+    // Center the curve at the origin and scale it to fit a unit square, keeping aspect ratio
+    pub normalize: bool,
+    // Join subpaths with straight pen-up segments, so that the pen travels to the next
+    // subpath instead of jumping there and the series has no discontinuity to fit
+    pub connect_subpaths: bool,
+}
+
+// Return value: the commands with every move after the first drawn segment turned into a
+// line from the previous end point, dropping moves that stay in place
+fn connect_subpaths(cmd_vec: Vec<CmdData>) -> Vec<CmdData> {
+    let mut connected = Vec::with_capacity(cmd_vec.len());
+    let mut drawn = false;
+    let mut end = Complex::new(0.0, 0.0);
+    for cmd in cmd_vec {
+        match cmd {
+            CmdData::Move(p) if drawn => {
+                if p != end {
+                    connected.push(CmdData::Line(p));
+                }
+            }
+            CmdData::Move(_) => connected.push(cmd),
+            _ => {
+                drawn = true;
+                connected.push(cmd);
+            }
+        }
+        end = connected.last().map_or(end, CmdData::end_point);
+    }
+    connected
+}
+
+// Number of chords each segment is measured with for arc length reparameterization
+const ARC_LENGTH_SAMPLES_PER_SEGMENT: usize = 64;
+
+// Return value: transform centering the traced curve at the origin within a unit square
+fn normalizing_transform(cmd_vec: &[CmdData]) -> AffineTransform {
+    let (mut min, mut max) = (
+        Complex::new(f64::INFINITY, f64::INFINITY),
+        Complex::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+    );
+    let mut start = Complex::new(0.0, 0.0);
+    for cmd in cmd_vec {
+        if let CmdData::Move(p0) = cmd {
+            start = *p0;
+            continue;
+        }
+
+        // Curves rarely reach their control points, so the bounds are sampled
+        for j in 0..=ARC_LENGTH_SAMPLES_PER_SEGMENT {
+            let p = cmd.point_at(start, j as f64 / ARC_LENGTH_SAMPLES_PER_SEGMENT as f64);
+            min = Complex::new(min.re.min(p.re), min.im.min(p.im));
+            max = Complex::new(max.re.max(p.re), max.im.max(p.im));
+        }
+        start = cmd.end_point();
+    }
+
+    let center = (min + max) / 2.0;
+    let size = (max.re - min.re).max(max.im - min.im);
+    let scale = if size > 0.0 && size.is_finite() {
+        1.0 / size
+    } else {
+        1.0
+    };
+    AffineTransform::scale(scale, scale)
+        .compose(&AffineTransform::translate(-center.re, -center.im))
+}
+
+// Return value: cumulative arc length at t = i / (number of segments * samples per segment)
+fn arc_length_table(cmd_vec: &[CmdData]) -> Vec<f64> {
+    let mut table = vec![0.0];
+    let mut length = 0.0;
+    let mut start = Complex::new(0.0, 0.0);
+    for cmd in cmd_vec {
+        if let CmdData::Move(p0) = cmd {
+            // Jumps between subpaths are not traced
+            start = *p0;
+            continue;
+        }
+
+        let mut prev = start;
+        for j in 1..=ARC_LENGTH_SAMPLES_PER_SEGMENT {
+            let p = cmd.point_at(start, j as f64 / ARC_LENGTH_SAMPLES_PER_SEGMENT as f64);
+            length += (p - prev).norm();
+            table.push(length);
+            prev = p;
+        }
+        start = cmd.end_point();
+    }
+
+    table
+}
+
+// Length of the drawn segments, leaving out the jumps between subpaths
+pub fn path_length(cmd_vec: &[CmdData]) -> f64 {
+    arc_length_table(cmd_vec).last().copied().unwrap_or(0.0)
+}
+
+// Warnings are printed to stderr
+// Return value: (path function, number of segments)
+pub fn parse_svg_into_proc<T: AsRef<std::path::Path>>(
+    path: T,
+) -> Result<(PathFnBox, usize), SvgParseError> {
+    let (func, segments_count, warnings) =
+        parse_svg_into_proc_with(path, &ParseOptions::default())?;
+    if !warnings.is_empty() {
+        eprintln!("SVG warning: {}", warnings);
+    }
+    Ok((func, segments_count))
+}
+
+// Unsupported commands are skipped rather than failing the whole SVG, as long as something
+// usable remains
+// Return value: (path function, number of segments, parts that were left out)
+pub fn parse_svg_into_proc_with<T: AsRef<std::path::Path>>(
+    path: T,
+    options: &ParseOptions,
+) -> Result<(PathFnBox, usize, ParseWarnings), SvgParseError> {
+    let mut content = String::new();
+    parse_svg_events_into_proc(svg::open(path, &mut content)?, options)
+}
+
+// Parses SVG content already in memory, e.g. fetched over the network or piped in
+// Return value: (path function, number of segments, parts that were left out)
+pub fn parse_svg_str_into_proc_with(
+    content: &str,
+    options: &ParseOptions,
+) -> Result<(PathFnBox, usize, ParseWarnings), SvgParseError> {
+    parse_svg_events_into_proc(svg::read(content)?, options)
+}
+
+// Return value: (path function, number of segments, parts that were left out)
+pub fn parse_svg_bytes_into_proc_with(
+    content: &[u8],
+    options: &ParseOptions,
+) -> Result<(PathFnBox, usize, ParseWarnings), SvgParseError> {
+    parse_svg_str_into_proc_with(std::str::from_utf8(content)?, options)
+}
+
+// Parses the SVG at path into its drawing commands without building a path function, e.g.
+// to inspect what was recognized
+// Warnings are printed to stderr
+pub fn parse_svg_commands<T: AsRef<std::path::Path>>(
+    path: T,
+) -> Result<Vec<CmdData>, SvgParseError> {
+    let (commands, warnings) = parse_svg_commands_with(path, &ParseOptions::default())?;
+    if !warnings.is_empty() {
+        eprintln!("SVG warning: {}", warnings);
+    }
+    Ok(commands)
+}
+
+// Return value: (commands in drawing order after applying options, parts that were left
+// out); arc_length does not change the commands, only how t is mapped onto them
+pub fn parse_svg_commands_with<T: AsRef<std::path::Path>>(
+    path: T,
+    options: &ParseOptions,
+) -> Result<(Vec<CmdData>, ParseWarnings), SvgParseError> {
+    let mut content = String::new();
+    parse_svg_events_into_commands(svg::open(path, &mut content)?, options)
+}
+
+// Parses the `d` attribute of a single path on its own, e.g. typed in by hand
+// Return value: (commands in drawing order after applying options, parts that were left out)
+pub fn parse_path_data_with(
+    data: &str,
+    options: &ParseOptions,
+) -> Result<(Vec<CmdData>, ParseWarnings), SvgParseError> {
+    let mut warnings = ParseWarnings::default();
+    let cmd_vec = path_data_commands(data, &mut warnings)?;
+    finish_commands(cmd_vec, warnings, options)
+}
+
+// Return value: (path function, number of segments, parts that were left out)
+pub fn parse_path_data_into_proc_with(
+    data: &str,
+    options: &ParseOptions,
+) -> Result<(PathFnBox, usize, ParseWarnings), SvgParseError> {
+    let (cmd_vec, warnings) = parse_path_data_with(data, options)?;
+    Ok(commands_into_proc(cmd_vec, warnings, options))
+}
+
+// Converts the `d` attribute of a path into absolute commands, recording the unsupported
+// ones in warnings
+fn path_data_commands(
+    data: &str,
+    warnings: &mut ParseWarnings,
+) -> Result<Vec<CmdData>, SvgParseError> {
+    use svg::node::element::path::Data;
+
+    let data = Data::parse(data).map_err(SvgParseError::InvalidPathData)?;
+    let mut commands = Vec::new();
+    let mut converter = CmdDataConverter::default();
+    // The current point is unknown after an unsupported command, so conversion
+    // resumes at the next absolute move
+    let mut lost_position = false;
+    for command in data.iter() {
+        let converted = match converter.convert(command) {
+            Ok(converted) => converted,
+            Err(SvgParseError::UnrecognizedCommand(letter)) => {
+                warnings.unsupported_commands.insert(letter);
+                lost_position = true;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if lost_position {
+            match command {
+                Command::Move(Position::Absolute, _) => lost_position = false,
+                _ => continue,
+            }
+        }
+        commands.extend(converted);
+    }
+    warnings.missing_move |= converter.missing_move();
+    Ok(commands)
+}
+
+// Return value: (commands after applying options, parts that were left out)
+fn parse_svg_events_into_commands(
+    events: svg::Parser,
+    options: &ParseOptions,
+) -> Result<(Vec<CmdData>, ParseWarnings), SvgParseError> {
+    use svg::node::element::tag;
+    use svg::parser::Event;
+
+    let mut cmd_vec: Vec<CmdData> = Vec::new();
+
+    let mut warnings = ParseWarnings::default();
+
+    // Transforms of the enclosing groups, innermost last
+    let mut group_transforms = vec![AffineTransform::identity()];
+
+    for event in events {
+        let parent_transform = *group_transforms.last().unwrap();
+        match event {
+            Event::Tag(tag::Group, tag::Type::Start, attributes) => {
+                group_transforms.push(element_transform(&parent_transform, &attributes)?);
+            }
+            Event::Tag(tag::Group, tag::Type::End, _) if group_transforms.len() > 1 => {
+                group_transforms.pop();
+            }
+            // Closing tags carry no attributes
+            Event::Tag(tag::Path, ty, attributes) if ty != tag::Type::End => {
+                let transform = element_transform(&parent_transform, &attributes)?;
+                let data = attributes.get("d").ok_or(SvgParseError::MissingData)?;
+                let converted = path_data_commands(data, &mut warnings)?;
+                cmd_vec.extend(converted.into_iter().map(|c| c.transformed(&transform)));
+            }
+            Event::Tag(name, ty, attributes)
+                if (name == tag::Polygon || name == tag::Polyline) && ty != tag::Type::End =>
+            {
+                let transform = element_transform(&parent_transform, &attributes)?;
+                let points = attributes
+                    .get("points")
+                    .ok_or(SvgParseError::MissingPoints)?;
+                let closed = name == tag::Polygon;
+                let converted = polyline_commands(&parse_points(points)?, closed);
+                cmd_vec.extend(converted.into_iter().map(|c| c.transformed(&transform)));
+            }
+            Event::Tag(name, ty, attributes)
+                if (name == tag::Circle || name == tag::Ellipse || name == tag::Rectangle)
+                    && ty != tag::Type::End =>
+            {
+                let transform = element_transform(&parent_transform, &attributes)?;
+                let converted = if name == tag::Rectangle {
+                    rect_element(&attributes)
+                } else {
+                    ellipse_element(&attributes, name == tag::Circle)
+                };
+                match converted {
+                    Ok(converted) => {
+                        cmd_vec.extend(converted.into_iter().map(|c| c.transformed(&transform)))
+                    }
+                    Err(LengthError::Relative) => warnings.relative_shape_skipped = true,
+                    Err(LengthError::Invalid) => return Err(SvgParseError::InvalidParameter),
+                }
+            }
+            // No font is available to turn glyphs into outlines, so text is skipped
+            Event::Tag(tag::Text, _, _) | Event::Tag(tag::TextPath, _, _) => {
+                warnings.text_skipped = true;
+            }
+            Event::Error(e) => return Err(SvgParseError::Malformed(e)),
+            _ => {}
+        }
+    }
+
+    finish_commands(cmd_vec, warnings, options)
+}
+
+// Applies options to the commands of the whole drawing, failing if nothing is drawn
+// Return value: (commands after applying options, parts that were left out)
+fn finish_commands(
+    mut cmd_vec: Vec<CmdData>,
+    warnings: ParseWarnings,
+    options: &ParseOptions,
+) -> Result<(Vec<CmdData>, ParseWarnings), SvgParseError> {
+    if options.connect_subpaths {
+        cmd_vec = connect_subpaths(cmd_vec);
+    }
+
+    // Move is not considered a segment
+    if cmd_vec.iter().all(|c| matches!(c, CmdData::Move(..))) {
+        if !warnings.unsupported_commands.is_empty() {
+            let commands = warnings.unsupported_commands.into_iter();
+            return Err(SvgParseError::OnlyUnsupportedCommands(
+                commands.collect::<Vec<_>>().join(", "),
+            ));
+        }
+        return Err(SvgParseError::NoPathFound);
+    }
+
+    if options.normalize {
+        let transform = normalizing_transform(&cmd_vec);
+        cmd_vec = cmd_vec
+            .into_iter()
+            .map(|c| c.transformed(&transform))
+            .collect();
+    }
+
+    Ok((cmd_vec, warnings))
+}
+
+// Return value: (path function, number of segments, parts that were left out)
+fn parse_svg_events_into_proc(
+    events: svg::Parser,
+    options: &ParseOptions,
+) -> Result<(PathFnBox, usize, ParseWarnings), SvgParseError> {
+    let (cmd_vec, warnings) = parse_svg_events_into_commands(events, options)?;
+    Ok(commands_into_proc(cmd_vec, warnings, options))
+}
+
+// Return value: (path function, number of segments, parts that were left out)
+fn commands_into_proc(
+    cmd_vec: Vec<CmdData>,
+    warnings: ParseWarnings,
+    options: &ParseOptions,
+) -> (PathFnBox, usize, ParseWarnings) {
+    let segments_count = cmd_vec
+        .iter()
+        .filter(|c| !matches!(c, CmdData::Move(..)))
+        .count();
+
+    let table = if options.arc_length {
+        Some(arc_length_table(&cmd_vec))
+    } else {
+        None
+    };
+
+    // Drawn segments along with the points they start from, so that evaluation indexes
+    // straight into the active segment instead of walking every command before it
+    let mut segments = Vec::with_capacity(segments_count);
+    let mut end = Complex::new(0.0, 0.0);
+    for cmd in cmd_vec {
+        let start = end;
+        end = cmd.end_point();
+        if let CmdData::Move(..) = cmd {
+            continue;
+        }
+        segments.push((start, cmd));
+    }
+
+    let func = move |t| {
+        let idx_prog = t * segments_count as f64;
+        let idx = idx_prog as usize;
+        let prog = idx_prog - idx as f64;
+
+        match segments.get(idx) {
+            Some((start, cmd)) => cmd.point_at(*start, prog),
+            // t = 1 and beyond stay at the very end
+            None => end,
+        }
+    };
+
+    match table {
+        Some(table) if *table.last().unwrap() > 0.0 => {
+            let total = *table.last().unwrap();
+            let last_idx = table.len() - 1;
+            let reparameterized = move |t: f64| {
+                let s = t.clamp(0.0, 1.0) * total;
+                let idx = table.partition_point(|&l| l < s).clamp(1, last_idx);
+                let (s0, s1) = (table[idx - 1], table[idx]);
+                let prog = if s1 > s0 { (s - s0) / (s1 - s0) } else { 0.0 };
+                func(((idx - 1) as f64 + prog) / last_idx as f64)
+            };
+            (Box::new(reparameterized), segments_count, warnings)
+        }
+        _ => (Box::new(func), segments_count, warnings),
+    }
+}
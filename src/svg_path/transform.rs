@@ -0,0 +1,99 @@
+use num::complex::Complex;
+
+// 2x3 affine matrix [a, b, c, d, e, f] in SVG order, mapping (x, y) to
+// (a * x + c * y + e, b * x + d * y + f)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AffineTransform(pub [f64; 6]);
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl AffineTransform {
+    pub fn identity() -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    pub fn translate(tx: f64, ty: f64) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, tx, ty])
+    }
+
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Self([sx, 0.0, 0.0, sy, 0.0, 0.0])
+    }
+
+    // Angle in degrees, clockwise on screen as y points downwards
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    // Transform applying inner first and then self, i.e. the matrix product self * inner
+    pub fn compose(&self, inner: &Self) -> Self {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = inner.0;
+        Self([
+            a1 * a2 + c1 * b2,
+            b1 * a2 + d1 * b2,
+            a1 * c2 + c1 * d2,
+            b1 * c2 + d1 * d2,
+            a1 * e2 + c1 * f2 + e1,
+            b1 * e2 + d1 * f2 + f1,
+        ])
+    }
+
+    pub fn apply(&self, p: Complex<f64>) -> Complex<f64> {
+        let [a, b, c, d, e, f] = self.0;
+        Complex::new(a * p.re + c * p.im + e, b * p.re + d * p.im + f)
+    }
+
+    // Parses a transform attribute such as `translate(10 20) scale(2)`, supporting matrix,
+    // translate, scale, rotate, skewX and skewY
+    // Return value: the combined transform, or a description of the invalid part
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut result = Self::identity();
+        let mut rest = s.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        while !rest.is_empty() {
+            let (name, args_and_rest) = rest
+                .split_once('(')
+                .ok_or_else(|| format!("expected `(` in `{}`", rest))?;
+            let (args, after) = args_and_rest
+                .split_once(')')
+                .ok_or_else(|| format!("expected `)` in `{}`", rest))?;
+            let args = args
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid arguments in `{}({})`", name.trim(), args))?;
+
+            let transform = match (name.trim(), &args[..]) {
+                ("matrix", &[a, b, c, d, e, f]) => Self([a, b, c, d, e, f]),
+                ("translate", &[tx]) => Self::translate(tx, 0.0),
+                ("translate", &[tx, ty]) => Self::translate(tx, ty),
+                ("scale", &[s]) => Self::scale(s, s),
+                ("scale", &[sx, sy]) => Self::scale(sx, sy),
+                ("rotate", &[angle]) => Self::rotate(angle),
+                ("rotate", &[angle, cx, cy]) => Self::translate(cx, cy)
+                    .compose(&Self::rotate(angle))
+                    .compose(&Self::translate(-cx, -cy)),
+                ("skewX", &[angle]) => Self([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", &[angle]) => Self([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                (name, args) => {
+                    return Err(format!(
+                        "unsupported transform `{}` with {} argument(s)",
+                        name,
+                        args.len()
+                    ))
+                }
+            };
+            // Transforms in a list apply from right to left
+            result = result.compose(&transform);
+            rest = after.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        }
+
+        Ok(result)
+    }
+}
@@ -1,3 +1,7 @@
+pub mod animation_clock;
 pub mod frame_history;
+pub mod path_editor;
+pub mod playlist;
+pub mod plot_theme;
 pub mod svg_select;
 pub mod window;
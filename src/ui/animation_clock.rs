@@ -0,0 +1,486 @@
+use eframe::egui;
+use std::{ops::RangeInclusive, time::Instant};
+
+// Frame steps per loop, matching the default trace resolution
+const STEP_COUNT: f64 = 1000.0;
+
+// Progress per second offered by the speed slider; the loop duration field offers the
+// reciprocal range, so that either control can reach every value of the other
+const SPEED_RANGE: RangeInclusive<f64> = 0.05..=2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlaybackMode {
+    Loop,
+    // Stops at the end
+    Once,
+    // Reverses direction at either end
+    PingPong,
+}
+
+// Playback state shared by the animated windows
+pub struct AnimationClock {
+    animate_start_t: Option<Instant>,
+    // Progress per second
+    animate_speed: f64,
+    t: f64,
+    mode: PlaybackMode,
+    // Whether t currently increases; toggled by the user and at the ends in ping-pong mode
+    forward: bool,
+    // t and direction at the last call of poll_cycle_end
+    polled: Option<(f64, bool)>,
+    // Whether a single playback ran to its end and stopped there; cleared when t moves again
+    finished: bool,
+}
+
+impl AnimationClock {
+    pub fn new(animate_speed: f64) -> Self {
+        Self {
+            animate_start_t: None,
+            animate_speed,
+            t: 0.0,
+            mode: PlaybackMode::Loop,
+            forward: true,
+            polled: None,
+            finished: false,
+        }
+    }
+
+    // Current position in [0, 1]
+    pub fn t(&self) -> f64 {
+        self.position().0
+    }
+
+    // Return value: the current t and whether it currently increases
+    fn position(&self) -> (f64, bool) {
+        let instant = match self.animate_start_t {
+            Some(instant) => instant,
+            None => return (self.t, self.forward),
+        };
+        let delta = instant.elapsed().as_secs_f64() * self.animate_speed;
+        let raw = if self.forward {
+            self.t + delta
+        } else {
+            self.t - delta
+        };
+        match self.mode {
+            PlaybackMode::Loop => (raw.rem_euclid(1.0), self.forward),
+            PlaybackMode::Once => (raw.clamp(0.0, 1.0), self.forward),
+            PlaybackMode::PingPong => {
+                // Positions in (1, 2) are the way back, mirrored
+                let phase = raw.rem_euclid(2.0);
+                if phase > 1.0 {
+                    (2.0 - phase, !self.forward)
+                } else {
+                    (phase, self.forward)
+                }
+            }
+        }
+    }
+
+    // Moves the elapsed progress into t, keeping the animation state otherwise
+    fn flush(&mut self) {
+        let (t, forward) = self.position();
+        self.t = t;
+        self.forward = forward;
+        if self.is_playing() {
+            self.animate_start_t = Some(Instant::now());
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        let t = self.t();
+        if self.forward {
+            t >= 1.0
+        } else {
+            t <= 0.0
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.animate_start_t = None;
+        self.t = 0.0;
+        self.forward = true;
+        self.polled = None;
+        self.finished = false;
+    }
+
+    // Meant to be called once per frame
+    // Return value: whether a cycle was completed while playing since the last call, i.e. t
+    // wrapped around in loop mode or turned at 0 in ping-pong mode
+    // A single playback has no cycles; see is_finished for its end
+    pub fn poll_cycle_end(&mut self) -> bool {
+        let (t, forward) = self.position();
+        let last = self.polled.replace((t, forward));
+        if !self.is_playing() {
+            return false;
+        }
+        match (self.mode, last) {
+            (PlaybackMode::Loop, Some((last_t, _))) => {
+                if forward {
+                    t < last_t
+                } else {
+                    t > last_t
+                }
+            }
+            // The way there and back from 0 ends where the direction flips to increasing t
+            (PlaybackMode::PingPong, Some((_, last_forward))) => !last_forward && forward,
+            _ => false,
+        }
+    }
+
+    // Pauses the animation at t
+    pub fn seek(&mut self, t: f64) {
+        self.animate_start_t = None;
+        self.t = t;
+        self.finished = false;
+    }
+
+    // Pauses and moves t by the given number of frame steps
+    pub fn step(&mut self, steps: i32) {
+        let t = self.t() + steps as f64 / STEP_COUNT;
+        self.seek(t.clamp(0.0, 1.0));
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.animate_speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        // Flush t so that the pen does not jump
+        self.flush();
+        self.animate_speed = speed;
+    }
+
+    pub fn set_mode(&mut self, mode: PlaybackMode) {
+        self.flush();
+        self.mode = mode;
+        self.finished = false;
+    }
+
+    // Flips the playback direction, keeping t in place
+    pub fn reverse(&mut self) {
+        self.flush();
+        self.forward = !self.forward;
+        self.finished = false;
+    }
+
+    pub fn play(&mut self) {
+        if !self.is_playing() {
+            // Start over after a single playback has finished
+            if self.mode == PlaybackMode::Once && self.is_at_end() {
+                self.t = if self.forward { 0.0 } else { 1.0 };
+            }
+            self.animate_start_t = Some(Instant::now());
+            self.finished = false;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        // Flush of t is necessary
+        self.flush();
+        self.animate_start_t = None;
+    }
+
+    // Pauses and returns the pen to the start, keeping the direction and mode
+    pub fn stop(&mut self) {
+        self.seek(0.0);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.animate_start_t.is_some()
+    }
+
+    // Whether a single playback has reached its end, as opposed to being paused or moved there
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Pauses at the end of a single playback, marking it finished
+    fn check_finished(&mut self) {
+        if self.is_playing() && self.mode == PlaybackMode::Once && self.is_at_end() {
+            self.pause();
+            self.finished = true;
+        }
+    }
+
+    // Draws the t slider followed by the playback buttons and the position in time
+    // Return value: response of the t slider
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        self.check_finished();
+        self.handle_shortcuts(ui);
+
+        let mut local_t = self.t();
+        // Without smart aim the slider seeks to exactly where it is dragged
+        let slider = egui::Slider::new(&mut local_t, 0.0..=1.0)
+            .clamp_to_range(true)
+            .smart_aim(false)
+            .fixed_decimals(4);
+        ui.label("Input of t:");
+
+        let slider_response = ui.add(slider);
+        if slider_response.changed() {
+            self.seek(local_t);
+        }
+
+        let control_btn_text = if self.is_playing() { "⏸" } else { "▶" };
+        if ui
+            .button(control_btn_text)
+            .on_hover_text("Play/pause (space)")
+            .clicked()
+        {
+            if self.is_playing() {
+                self.pause();
+            } else {
+                self.play();
+            }
+        }
+        if ui
+            .button("⏹")
+            .on_hover_text("Stop and return to the start")
+            .clicked()
+        {
+            self.stop();
+        }
+        if ui
+            .selectable_label(!self.forward, "⏪")
+            .on_hover_text("Play backwards")
+            .clicked()
+        {
+            self.reverse();
+        }
+        if ui
+            .small_button("⏮")
+            .on_hover_text("Step back (←)")
+            .clicked()
+        {
+            self.step(-1);
+        }
+        if ui
+            .small_button("⏭")
+            .on_hover_text("Step forward (→)")
+            .clicked()
+        {
+            self.step(1);
+        }
+
+        let t = self.t();
+        ui.label(format!(
+            "{:.1}% · {:.2} s / {:.2} s",
+            t * 100.0,
+            t / self.animate_speed,
+            1.0 / self.animate_speed
+        ));
+
+        slider_response
+    }
+
+    pub fn speed_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Speed:");
+        let mut speed = self.animate_speed;
+        let slider = egui::Slider::new(&mut speed, SPEED_RANGE)
+            .clamp_to_range(true)
+            .text("progress/s");
+        if ui.add(slider).changed() {
+            self.set_speed(speed);
+        }
+    }
+
+    // Space toggles playback and the arrow keys step t while the pointer is over the window
+    // of ui, so that several windows do not react to the same key
+    fn handle_shortcuts(&mut self, ui: &egui::Ui) {
+        let ctx = ui.ctx();
+        let hovered_layer = ctx
+            .input()
+            .pointer
+            .hover_pos()
+            .and_then(|pos| ctx.layer_id_at(pos));
+        // A focused widget takes the keys, e.g. space clicks a focused button
+        if hovered_layer != Some(ui.layer_id()) || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (toggle, back, forward) = {
+            let input = ctx.input();
+            (
+                input.key_pressed(egui::Key::Space),
+                input.key_pressed(egui::Key::ArrowLeft),
+                input.key_pressed(egui::Key::ArrowRight),
+            )
+        };
+        if toggle {
+            if self.is_playing() {
+                self.pause();
+            } else {
+                self.play();
+            }
+        }
+        if back {
+            self.step(-1);
+        }
+        if forward {
+            self.step(1);
+        }
+    }
+
+    pub fn mode_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Playback:");
+        let mut mode = self.mode;
+        ui.selectable_value(&mut mode, PlaybackMode::Loop, "Loop");
+        ui.selectable_value(&mut mode, PlaybackMode::Once, "Once");
+        ui.selectable_value(&mut mode, PlaybackMode::PingPong, "Ping-pong");
+        if mode != self.mode {
+            self.set_mode(mode);
+        }
+    }
+
+    pub fn duration_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Duration of one loop:");
+        let mut duration = 1.0 / self.animate_speed;
+        let drag = egui::DragValue::new(&mut duration)
+            .clamp_range(1.0 / SPEED_RANGE.end()..=1.0 / SPEED_RANGE.start())
+            .speed(0.1)
+            .suffix(" s");
+        if ui.add(drag).changed() {
+            self.set_speed(1.0 / duration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Time passes while the test runs, so positions are only compared this closely
+    const EPSILON: f64 = 1e-3;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "expected {}, found {}",
+            expected,
+            actual
+        );
+    }
+
+    // A clock that has been playing from t for the given number of seconds
+    fn playing_for(mode: PlaybackMode, t: f64, seconds: f64) -> AnimationClock {
+        let mut clock = AnimationClock::new(1.0);
+        clock.mode = mode;
+        clock.t = t;
+        clock.animate_start_t = Instant::now().checked_sub(Duration::from_secs_f64(seconds));
+        assert!(clock.is_playing());
+        clock
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let mut clock = playing_for(PlaybackMode::Loop, 0.5, 0.3);
+        assert_close(clock.t(), 0.8);
+        assert!(!clock.poll_cycle_end());
+
+        clock.animate_start_t = clock
+            .animate_start_t
+            .and_then(|instant| instant.checked_sub(Duration::from_secs_f64(0.45)));
+        assert_close(clock.t(), 0.25);
+        assert!(clock.poll_cycle_end());
+        assert!(!clock.poll_cycle_end());
+    }
+
+    #[test]
+    fn once_stops_at_the_end() {
+        let mut clock = playing_for(PlaybackMode::Once, 0.5, 3.0);
+        assert_eq!(clock.t(), 1.0);
+        assert!(clock.is_at_end());
+        assert!(!clock.poll_cycle_end());
+
+        clock.check_finished();
+        assert!(!clock.is_playing());
+        assert!(clock.is_finished());
+        assert_eq!(clock.t(), 1.0);
+
+        // Playing again after the end starts over
+        clock.play();
+        assert!(!clock.is_finished());
+        assert_close(clock.t(), 0.0);
+
+        // Pausing or seeking at the end is not a finished playback
+        let mut clock = playing_for(PlaybackMode::Once, 0.5, 0.1);
+        clock.check_finished();
+        assert!(clock.is_playing() && !clock.is_finished());
+        clock.seek(1.0);
+        clock.check_finished();
+        assert!(!clock.is_finished());
+    }
+
+    #[test]
+    fn ping_pong_reverses_at_the_ends() {
+        let clock = playing_for(PlaybackMode::PingPong, 0.5, 0.75);
+        let (t, forward) = clock.position();
+        assert_close(t, 0.75);
+        assert!(!forward);
+
+        let clock = playing_for(PlaybackMode::PingPong, 0.5, 1.75);
+        let (t, forward) = clock.position();
+        assert_close(t, 0.25);
+        assert!(forward);
+
+        // A cycle ends on turning at 0, not at 1
+        let mut clock = playing_for(PlaybackMode::PingPong, 0.0, 0.5);
+        assert!(!clock.poll_cycle_end());
+        clock.animate_start_t = clock
+            .animate_start_t
+            .and_then(|instant| instant.checked_sub(Duration::from_secs_f64(0.75)));
+        assert!(!clock.poll_cycle_end());
+        clock.animate_start_t = clock
+            .animate_start_t
+            .and_then(|instant| instant.checked_sub(Duration::from_secs_f64(1.0)));
+        assert_close(clock.t(), 0.25);
+        assert!(clock.poll_cycle_end());
+        assert!(!clock.poll_cycle_end());
+
+        // Pausing keeps the direction it was heading in
+        let mut clock = playing_for(PlaybackMode::PingPong, 0.5, 0.75);
+        clock.pause();
+        assert_close(clock.t, 0.75);
+        assert!(!clock.forward);
+    }
+
+    #[test]
+    fn seek_and_step_pause_at_the_new_t() {
+        let mut clock = playing_for(PlaybackMode::Loop, 0.0, 0.1);
+        clock.seek(0.5);
+        assert!(!clock.is_playing());
+        assert_eq!(clock.t(), 0.5);
+
+        clock.step(3);
+        assert_eq!(clock.t(), 0.5 + 3.0 / STEP_COUNT);
+        clock.step(-3);
+        assert_eq!(clock.t(), 0.5);
+
+        // Steps stay within [0, 1]
+        clock.seek(1.0 - 1.0 / STEP_COUNT);
+        clock.step(5);
+        assert_eq!(clock.t(), 1.0);
+        clock.seek(0.0);
+        clock.step(-1);
+        assert_eq!(clock.t(), 0.0);
+
+        let mut clock = playing_for(PlaybackMode::Loop, 0.2, 0.1);
+        clock.step(1);
+        assert!(!clock.is_playing());
+        assert_close(clock.t(), 0.3 + 1.0 / STEP_COUNT);
+    }
+
+    #[test]
+    fn stop_returns_to_the_start() {
+        let mut clock = playing_for(PlaybackMode::PingPong, 0.5, 0.25);
+        clock.reverse();
+        clock.stop();
+        assert!(!clock.is_playing());
+        assert_eq!(clock.t(), 0.0);
+        // Unlike reset, the direction and mode are kept
+        assert!(!clock.forward);
+        assert_eq!(clock.mode, PlaybackMode::PingPong);
+    }
+}
@@ -0,0 +1,64 @@
+use eframe::egui;
+
+// Seconds without typing before the path data is parsed again
+const DEBOUNCE_DELAY: f64 = 0.3;
+
+// Text box for raw path data, e.g. to try out commands without writing files
+pub struct PathEditor {
+    data: String,
+    // Time of the last edit that has not been parsed yet
+    edited_at: Option<f64>,
+    // Outcome of the last parse if it needs attention, i.e. an error or warnings
+    status: Option<String>,
+}
+
+impl Default for PathEditor {
+    fn default() -> Self {
+        Self {
+            data: "M 0 0 L 100 0 C 150 50 50 100 0 100 Z".to_owned(),
+            edited_at: None,
+            status: None,
+        }
+    }
+}
+
+impl PathEditor {
+    // Draws the text box along with the status of the last parse
+    // Return value: whether the path data should be parsed and previewed, i.e. typing has
+    // paused or the preview was requested
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let now = ui.input().time;
+        let mut apply = false;
+        egui::CollapsingHeader::new("Edit path data").show(ui, |ui| {
+            ui.small("The `d` attribute of an SVG path, previewed as you type");
+            if ui.text_edit_multiline(&mut self.data).changed() {
+                self.edited_at = Some(now);
+            }
+            if ui.button("Preview").clicked() {
+                apply = true;
+            }
+            if let Some(status) = &self.status {
+                ui.colored_label(egui::Color32::YELLOW, status);
+            }
+        });
+        // Checked outside the header, so that an edit still applies if it is collapsed
+        match self.edited_at {
+            Some(edited_at) if now - edited_at >= DEBOUNCE_DELAY => apply = true,
+            // Wake up again once the delay is over
+            Some(_) => ui.ctx().request_repaint(),
+            None => {}
+        }
+        if apply {
+            self.edited_at = None;
+        }
+        apply
+    }
+
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    pub fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+    }
+}
@@ -0,0 +1,63 @@
+use eframe::egui;
+use std::path::Path;
+
+// Files whose series the animation draws one after another
+#[derive(Default)]
+pub struct Playlist {
+    paths: Vec<String>,
+}
+
+impl Playlist {
+    // Lists the queued files with buttons to reorder and remove them, and one adding selected
+    // Return value: whether the playlist should be calculated
+    pub fn ui(&mut self, ui: &mut egui::Ui, selected: Option<&String>, allowed: bool) -> bool {
+        let mut calculate = false;
+        egui::CollapsingHeader::new(format!("Playlist ({})", self.paths.len())).show(ui, |ui| {
+            let mut moved_up = None;
+            let mut removed = None;
+            for (i, path) in self.paths.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let file_name = Path::new(path)
+                        .file_name()
+                        .map_or_else(|| path.clone(), |s| s.to_string_lossy().into_owned());
+                    ui.label(format!("{}. {}", i + 1, file_name))
+                        .on_hover_text(path);
+                    if i > 0 && ui.small_button("⏶").on_hover_text("Move up").clicked() {
+                        moved_up = Some(i);
+                    }
+                    if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = moved_up {
+                self.paths.swap(i - 1, i);
+            }
+            if let Some(i) = removed {
+                self.paths.remove(i);
+            }
+
+            ui.horizontal(|ui| {
+                ui.scope(|ui| {
+                    ui.set_enabled(selected.is_some());
+                    if ui.button("Add selected file").clicked() {
+                        self.paths.extend(selected.cloned());
+                    }
+                });
+                if ui.button("Clear").clicked() {
+                    self.paths.clear();
+                }
+                ui.set_enabled(allowed && !self.paths.is_empty());
+                calculate = ui
+                    .button("Calculate playlist")
+                    .on_hover_text("Draw each file in turn, switching whenever one is drawn")
+                    .clicked();
+            });
+        });
+        calculate
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+}
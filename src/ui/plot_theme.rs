@@ -0,0 +1,100 @@
+use eframe::egui;
+
+// Visual preset for plots, independent of the global egui theme
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum PlotTheme {
+    #[default]
+    FollowApp,
+    Light,
+    Dark,
+}
+
+impl PlotTheme {
+    // Applies the preset to ui, which should be a scope wrapping only the plot
+    pub fn apply(self, ui: &mut egui::Ui) {
+        match self {
+            PlotTheme::FollowApp => {}
+            PlotTheme::Light => *ui.visuals_mut() = egui::Visuals::light(),
+            PlotTheme::Dark => *ui.visuals_mut() = egui::Visuals::dark(),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Plot theme:");
+            ui.selectable_value(self, PlotTheme::FollowApp, "Follow app");
+            ui.selectable_value(self, PlotTheme::Light, "☀ Light");
+            ui.selectable_value(self, PlotTheme::Dark, "🌙 Dark");
+        });
+    }
+}
+
+// Plot furniture that can be hidden for a clean view, e.g. when recording
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlotDecorations {
+    // Grid lines with their axis labels
+    pub show_axes: bool,
+    pub show_background: bool,
+}
+
+impl Default for PlotDecorations {
+    fn default() -> Self {
+        Self {
+            show_axes: true,
+            show_background: true,
+        }
+    }
+}
+
+impl PlotDecorations {
+    pub fn apply(self, plot: egui::plot::Plot) -> egui::plot::Plot {
+        plot.show_axes([self.show_axes; 2])
+            .show_background(self.show_background)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.show_axes, "Grid and axes");
+        ui.checkbox(&mut self.show_background, "Background");
+    }
+}
+
+// Thickness of what is drawn, e.g. bold for a screencast or thin for close inspection
+#[derive(Clone, Copy, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct StrokeStyle {
+    pub trace_width: f32,
+    // Plot arrows come in two widths only, 1 and 2 points
+    pub bold_arrows: bool,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            trace_width: 1.0,
+            bold_arrows: false,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub const TRACE_WIDTH_RANGE: std::ops::RangeInclusive<f32> = 0.5..=8.0;
+
+    pub fn apply_to_arrows(self, arrows: egui::plot::Arrows) -> egui::plot::Arrows {
+        if self.bold_arrows {
+            arrows.highlight()
+        } else {
+            arrows
+        }
+    }
+
+    // The arrow toggle is left out for plots without arrows
+    pub fn ui(&mut self, ui: &mut egui::Ui, with_arrows: bool) {
+        ui.add(
+            egui::Slider::new(&mut self.trace_width, Self::TRACE_WIDTH_RANGE)
+                .clamp_to_range(true)
+                .text("Trace width"),
+        );
+        if with_arrows {
+            ui.checkbox(&mut self.bold_arrows, "Bold arrows");
+        }
+    }
+}
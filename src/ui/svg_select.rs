@@ -1,22 +1,81 @@
 use eframe::egui;
+use std::path::Path;
 
+// Length of the recent files list
+const MAX_RECENT: usize = 8;
+
+#[derive(Default)]
 pub struct SvgSelect {
     pub disp_path: Option<String>,
-}
-
-impl Default for SvgSelect {
-    fn default() -> Self {
-        Self { disp_path: None }
-    }
+    // Most recently selected first
+    recent: Vec<String>,
 }
 
 impl SvgSelect {
     pub fn ui(&mut self, ui: &mut egui::Ui) {
-        ui.label("Try dragging a svg into the window.");
-        if let Some(path) = &self.disp_path {
-            ui.label(format!("Selected svg: {}", path));
-        } else {
-            ui.label("No svg is selected.");
+        ui.label(
+            "Try dragging a svg (or a csv/txt of exported samples or x,y points) into the window.",
+        );
+        ui.label("Coefficient files (json of freq/amp/phase vectors) can be dropped too.");
+        ui.horizontal(|ui| {
+            if let Some(path) = &self.disp_path {
+                ui.label(format!("Selected svg: {}", path));
+            } else {
+                ui.label("No svg is selected.");
+            }
+            // For setups where dragging files into the window does not work
+            if ui.button("Browse...").clicked() {
+                let picked = rfd::FileDialog::new()
+                    .add_filter("SVG", &["svg"])
+                    .add_filter("Samples or points", &["csv", "txt"])
+                    .pick_file();
+                if let Some(path) = picked {
+                    self.select(path.display().to_string());
+                }
+            }
+        });
+
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            if !self.recent.is_empty() {
+                ui.label("Recent:");
+            }
+            for path in &self.recent {
+                let file_name = Path::new(path)
+                    .file_name()
+                    .map_or_else(|| path.clone(), |s| s.to_string_lossy().into_owned());
+                let selected = self.disp_path.as_ref() == Some(path);
+                if ui
+                    .selectable_label(selected, file_name)
+                    .on_hover_text(path)
+                    .clicked()
+                {
+                    clicked = Some(path.clone());
+                }
+            }
+        });
+        if let Some(path) = clicked {
+            self.select(path);
         }
     }
+
+    // Sets the selected path and moves it to the front of the recent files
+    pub fn select(&mut self, path: String) {
+        let mut recent = std::mem::take(&mut self.recent);
+        recent.retain(|p| *p != path);
+        recent.insert(0, path.clone());
+        self.set_recent_files(recent);
+        self.disp_path = Some(path);
+    }
+
+    pub fn recent_files(&self) -> &[String] {
+        &self.recent
+    }
+
+    // Files that no longer exist are dropped
+    pub fn set_recent_files(&mut self, mut recent: Vec<String>) {
+        recent.retain(|p| Path::new(p).exists());
+        recent.truncate(MAX_RECENT);
+        self.recent = recent;
+    }
 }
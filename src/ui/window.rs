@@ -1,20 +1,93 @@
 use eframe::egui;
+use num::Complex;
+use std::time::Instant;
 
 pub mod fourier_animation;
 pub mod svg_preview;
 
+pub const MAX_TRACE_POINTS: usize = 16000;
+
+// Lets the user trade trace smoothness against drawing cost
+pub fn trace_points_ui(ui: &mut egui::Ui, trace_points: &mut usize) {
+    ui.label("Trace points:");
+    ui.add(
+        egui::DragValue::new(trace_points)
+            .clamp_range(100..=MAX_TRACE_POINTS)
+            .speed(10.0),
+    )
+    .on_hover_text("Increase if the curve looks jagged");
+}
+
+// Shows the point at t with a button copying it, e.g. "0.123456+0.654321i"
+// Shift-clicking copies t along with it
+pub fn output_ui(ui: &mut egui::Ui, t: f64, point: Complex<f64>) {
+    ui.horizontal(|ui| {
+        let text = format!("{:.6}", point);
+        ui.label(format!("Output: {}", text));
+        if ui
+            .small_button("📋")
+            .on_hover_text("Copy to clipboard, hold Shift to include t")
+            .clicked()
+        {
+            ui.output().copied_text = if ui.input().modifiers.shift {
+                format!("t = {:.6}: {}", t, text)
+            } else {
+                text
+            };
+        }
+    });
+}
+
+// Time spent building a window's ui, averaged over the last second
+pub struct UiTiming {
+    times: egui::util::History<f32>,
+}
+
+impl Default for UiTiming {
+    fn default() -> Self {
+        Self {
+            times: egui::util::History::new(300, 1.0),
+        }
+    }
+}
+
+impl UiTiming {
+    pub fn add(&mut self, now: f64, seconds: f32) {
+        self.times.add(now, seconds);
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.times.average().unwrap_or_default()
+    }
+}
+
 pub trait Window {
     fn name(&self) -> &'static str;
 
+    // Measures the ui into timing and shows the result in the window if timing is given
     // Return value: is ui drawn
-    fn show(&mut self, ctx: &egui::CtxRef, open: &mut bool) -> bool {
+    fn show(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        mut timing: Option<&mut UiTiming>,
+    ) -> bool {
         let mut ui_drawn = false;
-        egui::Window::new(self.name())
+        let name = self.name();
+        egui::Window::new(name)
             .open(open)
             .default_size(egui::vec2(512.0, 256.0))
             .show(ctx, |ui| {
                 ui_drawn = true;
-                self.ui(ui)
+                if let Some(timing) = &timing {
+                    ui.small(format!("{}: {:.2} ms", name, 1e3 * timing.mean()))
+                        .on_hover_text("Time spent laying out this window, excluding tessellation");
+                }
+                let start = Instant::now();
+                self.ui(ui);
+                if let Some(timing) = &mut timing {
+                    timing.add(ui.input().time, start.elapsed().as_secs_f32());
+                }
             });
         ui_drawn
     }
@@ -1,24 +1,123 @@
-use crate::util::math::FourierSeriesDesc;
+use crate::{
+    app_config::AppConfig,
+    render::vector,
+    ui::{
+        animation_clock::AnimationClock,
+        plot_theme::{PlotDecorations, PlotTheme, StrokeStyle},
+    },
+    util::{math::FourierSeriesDesc, samples::write_samples_to, transform::PlaneTransform},
+};
 use eframe::egui::{self, plot::Arrows};
-use egui::plot::{Line, Plot, Value, Values};
-use num::complex::Complex;
-use std::{cmp::Ordering, iter, time::Instant};
+use egui::plot::{Line, Plot, VLine, Value, Values};
+use num::Complex;
+use std::{io::Write, iter};
 
 pub struct FourierAnimationWindow {
     series_desc: Option<FourierSeriesDesc<f64>>,
-    animate_start_t: Option<Instant>,
-    // Progress per second
-    animate_speed: f64,
-    t: f64,
+    // Frequencies of the full series in the order the arrows are chained
+    epicycle_order: Vec<isize>,
+    // Number of central coefficients shown, letting the series be truncated live
+    visible_n: usize,
+    // Damp the Gibbs overshoot of the shown series with Lanczos sigma factors
+    lanczos_smoothing: bool,
+    // RMS distance from the source path, if known
+    approximation_error: Option<f64>,
+    // Outcome of the last save
+    save_msg: Option<String>,
+    // Source path sampled evenly over t, drawn for comparison and measured against in the
+    // residual plot
+    target: Option<Vec<Complex<f64>>>,
+    show_target: bool,
+    plot_theme: PlotTheme,
+    plot_decorations: PlotDecorations,
+    stroke_style: StrokeStyle,
+    transform: PlaneTransform,
+    // Scale the number of trace points with the highest frequency
+    adaptive_sampling: bool,
+    show_circles: bool,
+    // The arrow chain and the pen trace can be hidden independently, e.g. to focus on either
+    show_arrows: bool,
+    show_trace: bool,
+    // Fade the arrows and circles out once a single playback finishes, keeping the drawing
+    fade_arrows: bool,
+    // Seconds the fade takes
+    fade_duration: f64,
+    // Time at which the current finished playback was first shown
+    finished_at: Option<f64>,
+    // Start the arrow chain at the centroid, leaving out the arrow of the constant term
+    center_epicycles: bool,
+    // Arrows shorter than this fraction of the curve's size are merged into the next one
+    arrow_threshold: f64,
+    // Color the trace by t instead of a flat color
+    gradient_trace: bool,
+    // Trace resolution, or its minimum with adaptive sampling
+    trace_points: usize,
+    trace_cache: TraceCache,
+    // Resolution of the signal plots and the copied samples
+    signal_samples: usize,
+    // Fit the plot to the whole curve rather than the part drawn so far
+    lock_view: bool,
+    overlays: Vec<Overlay>,
+    // Overlay the series is morphed into, and how far, from 0 for the series itself to 1 for
+    // the overlay
+    morph_target: Option<usize>,
+    morph_alpha: f64,
+    // Series drawn in turn, switching at the end of each loop; empty outside playlist mode
+    playlist: Vec<PlaylistEntry>,
+    // Entry of the playlist currently shown
+    playlist_index: usize,
+    clock: AnimationClock,
+}
+
+// A series of a playlist along with what is set for it when it is shown
+pub struct PlaylistEntry {
+    pub name: String,
+    pub desc: FourierSeriesDesc<f64>,
+    pub approximation_error: Option<f64>,
+    pub target: Option<Vec<Complex<f64>>>,
 }
 
 impl Default for FourierAnimationWindow {
     fn default() -> Self {
+        Self::new(&AppConfig::default())
+    }
+}
+
+impl FourierAnimationWindow {
+    pub fn new(config: &AppConfig) -> Self {
         FourierAnimationWindow {
             series_desc: None,
-            animate_start_t: None,
-            animate_speed: 0.2,
-            t: 0.0,
+            epicycle_order: Vec::new(),
+            visible_n: 0,
+            lanczos_smoothing: false,
+            approximation_error: None,
+            save_msg: None,
+            target: None,
+            show_target: false,
+            plot_theme: Default::default(),
+            plot_decorations: Default::default(),
+            stroke_style: Default::default(),
+            transform: Default::default(),
+            adaptive_sampling: false,
+            show_circles: true,
+            show_arrows: true,
+            show_trace: true,
+            fade_arrows: false,
+            fade_duration: 1.0,
+            finished_at: None,
+            center_epicycles: false,
+            arrow_threshold: 0.002,
+            gradient_trace: false,
+            trace_points: config.trace_points,
+            signal_samples: config.signal_samples,
+            trace_cache: Default::default(),
+            lock_view: false,
+            overlays: Vec::new(),
+            morph_target: None,
+            morph_alpha: 0.0,
+            playlist: Vec::new(),
+            playlist_index: 0,
+            clock: AnimationClock::new(config.animation_speed),
         }
     }
 }
@@ -29,142 +128,975 @@ impl super::Window for FourierAnimationWindow {
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
+        // Every mode moves on to the next series once the current one is done
+        let cycle_ended = self.clock.poll_cycle_end();
+        if self.playlist.len() > 1 {
+            let next = (self.playlist_index + 1) % self.playlist.len();
+            let now = ui.input().time;
+            let faded = !self.fade_arrows
+                || self
+                    .finished_at
+                    .is_some_and(|finished_at| now - finished_at >= self.fade_duration);
+            if cycle_ended {
+                self.show_playlist_entry(next);
+            } else if self.clock.is_finished() && faded && next != 0 {
+                // A single playback goes through the playlist once, stopping after the last
+                self.show_playlist_entry(next);
+                self.clock.play();
+            }
+        }
+        let mut shown_entry = None;
+        // Morph settings changed this frame, applied at the end so the trace cache can be
+        // cleared before the next frame draws the new shape
+        let mut morph_update = None;
+
         let Self {
             series_desc,
-            animate_start_t,
-            animate_speed,
-            t,
+            epicycle_order,
+            visible_n,
+            lanczos_smoothing,
+            approximation_error,
+            save_msg,
+            target,
+            show_target,
+            plot_theme,
+            plot_decorations,
+            stroke_style,
+            transform,
+            adaptive_sampling,
+            show_circles,
+            show_arrows,
+            show_trace,
+            fade_arrows,
+            fade_duration,
+            finished_at,
+            center_epicycles,
+            arrow_threshold,
+            gradient_trace,
+            trace_points,
+            trace_cache,
+            signal_samples,
+            lock_view,
+            overlays,
+            morph_target,
+            morph_alpha,
+            playlist,
+            playlist_index,
+            clock,
         } = self;
 
-        let mut local_t = if let Some(instant) = animate_start_t {
-            (*t + instant.elapsed().as_secs_f64() * *animate_speed).fract()
-        } else {
-            *t
-        };
+        // Everything below works on the truncated series, saving included
+        let full_n = series_desc.as_ref().map_or(0, |d| d.as_vec().len());
+        let morph = morph_target.and_then(|i| overlays.get(i));
+        let shown_desc = series_desc.as_ref().map(|d| match morph {
+            // Only series of equal length interpolate, so both are cut to the smaller one
+            Some(overlay) => {
+                let n = (*visible_n).min(overlay.desc.as_vec().len());
+                d.truncated(n)
+                    .lerp(&overlay.desc.truncated(n), *morph_alpha)
+            }
+            None => d.truncated(*visible_n),
+        });
+        let shown_desc = shown_desc.map(|d| {
+            if *lanczos_smoothing {
+                d.with_lanczos_smoothing()
+            } else {
+                d
+            }
+        });
+        let shown_n = shown_desc.as_ref().map_or(0, |d| d.as_vec().len());
+        // The error and the residual compare the series against its own source
+        let morphing = morph.is_some();
 
-        if let Some(desc) = series_desc {
-            let func = desc.as_fn();
+        ui.horizontal(|ui| {
+            ui.set_enabled(shown_desc.is_some());
+            if ui.button("Save coefficients").clicked() {
+                if let Some(msg) = shown_desc.as_ref().and_then(save_series) {
+                    *save_msg = Some(msg);
+                }
+            }
+            if ui.button("Export CSV").clicked() {
+                if let Some(msg) = shown_desc.as_ref().and_then(export_csv) {
+                    *save_msg = Some(msg);
+                }
+            }
+            if ui.button("Export SVG").clicked() {
+                if let Some(msg) = shown_desc.as_ref().and_then(|d| export_svg(d, transform)) {
+                    *save_msg = Some(msg);
+                }
+            }
+            if let Some(msg) = save_msg {
+                ui.label(msg.as_str());
+            }
+        });
 
-            ui.horizontal(|ui| {
-                let mut animation_should_stop = false;
-                let animation_running = animate_start_t.is_some();
-                let slider = egui::Slider::new(&mut local_t, 0.0..=1.0).clamp_to_range(true);
-                ui.label("Input of t:");
+        if let Some(desc) = &shown_desc {
+            let series_fn = desc.as_fn();
+            let func = |t| transform.apply(series_fn(t));
 
+            ui.horizontal(|ui| clock.ui(ui));
+            ui.horizontal(|ui| {
+                clock.speed_ui(ui);
+                clock.duration_ui(ui);
+            });
+            ui.horizontal(|ui| clock.mode_ui(ui));
+            if !playlist.is_empty() {
+                ui.horizontal(|ui| {
+                    let entry = &playlist[*playlist_index];
+                    ui.label(format!(
+                        "Playlist: {} of {} ({})",
+                        *playlist_index + 1,
+                        playlist.len(),
+                        entry.name
+                    ))
+                    .on_hover_text(
+                        "The next series is drawn after each loop, single playback or \
+                        ping-pong cycle",
+                    );
+                    if ui.small_button("⏮").on_hover_text("Previous").clicked() {
+                        shown_entry = Some((*playlist_index + playlist.len() - 1) % playlist.len());
+                    }
+                    if ui.small_button("⏭").on_hover_text("Next").clicked() {
+                        shown_entry = Some((*playlist_index + 1) % playlist.len());
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                let mut max_frequency = (*visible_n - 1) / 2;
+                let slider = egui::Slider::new(&mut max_frequency, 0..=(full_n - 1) / 2)
+                    .clamp_to_range(true)
+                    .text("Max frequency");
                 if ui.add(slider).changed() {
-                    *animate_start_t = None;
-                    animation_should_stop = true;
+                    *visible_n = max_frequency * 2 + 1;
                 }
-
-                let control_btn_text = if animation_running { "⏸" } else { "▶" };
-                if ui.button(control_btn_text).clicked() {
-                    if animation_running {
-                        *animate_start_t = None;
-                        animation_should_stop = true;
-                    } else {
-                        *animate_start_t = Some(Instant::now());
+                ui.label(format!("({} of {} terms)", visible_n, full_n));
+                if ui
+                    .checkbox(lanczos_smoothing, "Lanczos smoothing")
+                    .on_hover_text("Reduce ringing at corners, rounding them slightly")
+                    .changed()
+                {
+                    trace_cache.clear();
+                }
+            });
+            ui.horizontal(|ui| {
+                super::trace_points_ui(ui, trace_points);
+                ui.checkbox(adaptive_sampling, "Adaptive trace sampling")
+                    .on_hover_text("Use more trace points for series with higher frequencies");
+                ui.checkbox(show_arrows, "Show arrows");
+                ui.checkbox(show_circles, "Show circles");
+                ui.checkbox(show_trace, "Show trace");
+                ui.checkbox(fade_arrows, "Fade out at the end")
+                    .on_hover_text(
+                        "Fade the arrows out after a single playback, leaving the drawing",
+                    );
+                if *fade_arrows {
+                    let drag = egui::DragValue::new(fade_duration)
+                        .clamp_range(0.1..=10.0)
+                        .speed(0.05)
+                        .suffix(" s");
+                    ui.add(drag).on_hover_text("Duration of the fade");
+                }
+                ui.checkbox(center_epicycles, "Center epicycles")
+                    .on_hover_text(
+                        "Start the arrows at the shape's centroid instead of the origin",
+                    );
+                ui.checkbox(gradient_trace, "Gradient trace")
+                    .on_hover_text("Color the trace by t to show the drawing order");
+                if target.is_some() {
+                    ui.checkbox(show_target, "Show target");
+                }
+            });
+            ui.horizontal(|ui| {
+                plot_decorations.ui(ui);
+                ui.checkbox(lock_view, "Lock view")
+                    .on_hover_text("Keep the camera still while the pen draws");
+            });
+            ui.horizontal(|ui| stroke_style.ui(ui, true));
+            ui.horizontal(|ui| {
+                let mut percent = *arrow_threshold * 100.0;
+                let slider = egui::Slider::new(&mut percent, 0.0..=5.0)
+                    .logarithmic(true)
+                    .suffix("%")
+                    .text("Arrow visibility threshold");
+                if ui
+                    .add(slider)
+                    .on_hover_text("Hide arrows shorter than this fraction of the curve's size")
+                    .changed()
+                {
+                    *arrow_threshold = percent / 100.0;
+                }
+            });
+            if !overlays.is_empty() {
+                egui::CollapsingHeader::new(format!("Overlays ({})", overlays.len())).show(
+                    ui,
+                    |ui| {
+                        let mut removed = None;
+                        for (i, overlay) in overlays.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                let color = OVERLAY_COLORS[i % OVERLAY_COLORS.len()];
+                                let n = overlay.desc.as_vec().len();
+                                ui.colored_label(color, format!("Series {} ({} terms)", i + 1, n));
+                                ui.checkbox(&mut overlay.show_epicycles, "Epicycles");
+                                if ui.button("Remove").clicked() {
+                                    removed = Some(i);
+                                }
+                            });
+                        }
+                        if let Some(i) = removed {
+                            overlays.remove(i);
+                            // Indices after i shift, so the morph is stopped rather than fixed up
+                            morph_update = Some((None, *morph_alpha));
+                        }
+                        if ui.button("Remove all").clicked() {
+                            overlays.clear();
+                            morph_update = Some((None, *morph_alpha));
+                        }
+                    },
+                );
+                ui.horizontal(|ui| {
+                    let (mut target, mut alpha) = (*morph_target, *morph_alpha);
+                    ui.label("Morph into:");
+                    ui.selectable_value(&mut target, None, "None");
+                    for i in 0..overlays.len() {
+                        ui.selectable_value(&mut target, Some(i), format!("Series {}", i + 1));
                     }
+                    ui.set_enabled(target.is_some());
+                    ui.add(
+                        egui::Slider::new(&mut alpha, 0.0..=1.0)
+                            .clamp_to_range(true)
+                            .text("alpha"),
+                    )
+                    .on_hover_text("Interpolate the coefficients from this series to the overlay");
+                    if (target, alpha) != (*morph_target, *morph_alpha) {
+                        morph_update = Some((target, alpha));
+                    }
+                });
+            }
+
+            // At least a few points per shortest wavelength (1 / max |k|), within limits
+            const SAMPLES_PER_WAVELENGTH: usize = 8;
+            let trace_samples = if *adaptive_sampling {
+                (desc.max_frequency() * SAMPLES_PER_WAVELENGTH)
+                    .clamp(*trace_points, super::MAX_TRACE_POINTS)
+            } else {
+                *trace_points
+            };
+            // Shorter arrows would be a few pixels at most when the whole curve is in view
+            let (min, max) = trace_cache.bounds(&series_fn, (shown_n, trace_samples));
+            let min_arrow_length = (max.re - min.re).max(max.im - min.im) * *arrow_threshold;
+
+            let local_t = clock.t();
+            super::output_ui(ui, local_t, func(local_t));
+
+            // The fade starts over whenever a playback finishes anew
+            let now = ui.input().time;
+            if !clock.is_finished() {
+                *finished_at = None;
+            } else if finished_at.is_none() {
+                *finished_at = Some(now);
+            }
+            // Opacity of the arrows and circles
+            let arrow_opacity = match finished_at {
+                Some(finished_at) if *fade_arrows => {
+                    (1.0 - (now - *finished_at) / *fade_duration).max(0.0)
+                }
+                _ => 1.0,
+            };
+            // The next frame moves on to the next series of the playlist, if there is one
+            let advancing = *playlist_index + 1 < playlist.len();
+            if finished_at.is_some() && ((*fade_arrows && arrow_opacity > 0.0) || advancing) {
+                // Playback has stopped, so nothing else asks for the next frame
+                ui.ctx().request_repaint();
+            }
+            let arrow_color = fade_arrows.then(|| fading_color(ARROW_COLOR, arrow_opacity));
+            // The constant term does not rotate, so it is not counted as an epicycle
+            let magnitudes = desc.harmonics().into_iter().filter(|h| h.0 != 0);
+            let epicycles_count = magnitudes.clone().count();
+            let visible_count = magnitudes.filter(|h| h.1 >= min_arrow_length).count();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Epicycles above threshold: {} of {}",
+                    visible_count, epicycles_count
+                ));
+                if visible_count * 2 < epicycles_count {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        "Most epicycles are negligible, a lower n would look the same",
+                    );
                 }
+            });
+            // The error was measured for the full series
+            if let Some(error) = approximation_error
+                .filter(|_| *visible_n == full_n && !morphing && !*lanczos_smoothing)
+            {
+                ui.label(format!("Approximation error (RMS): {:.6}", error))
+                    .on_hover_text("Increase n if the error is too large");
+            }
+
+            // Only known for series integrated adaptively
+            if let Some(max_error) = desc.max_integration_error() {
+                let max_frequency = desc.max_frequency() as isize;
+                let worst_k = (-max_frequency..=max_frequency)
+                    .zip(desc.integration_errors())
+                    .find(|&(_, &error)| error == max_error)
+                    .map_or(0, |(k, _)| k);
+                ui.label(format!(
+                    "Max coefficient integration error: {:.3e} (k = {})",
+                    max_error, worst_k
+                ))
+                .on_hover_text(
+                    "Estimated quadrature error of the least accurate coefficient. \
+                    Large values mean features too sharp for the integrator to resolve.",
+                );
+            }
+
+            let unconverged = desc.unconverged_frequencies();
+            if !unconverged.is_empty() {
+                let frequencies: Vec<_> = unconverged.iter().map(|k| k.to_string()).collect();
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "Warning: integration did not converge for frequencies {}. \
+                        Try a higher recursion depth or a lower n.",
+                        frequencies.join(", "),
+                    ),
+                );
+            }
 
-                // Flush t where necessary
-                if animation_should_stop {
-                    *t = local_t;
+            egui::CollapsingHeader::new("Signals").show(ui, |ui| {
+                let samples: Vec<_> = (0..=*signal_samples)
+                    .map(|i| {
+                        let t = i as f64 / *signal_samples as f64;
+                        (t, func(t))
+                    })
+                    .collect();
+                let x_line = Line::new(Values::from_values_iter(
+                    samples.iter().map(|&(t, p)| Value::new(t, p.re)),
+                ))
+                .name("x(t)");
+                let y_line = Line::new(Values::from_values_iter(
+                    samples.iter().map(|&(t, p)| Value::new(t, p.im)),
+                ))
+                .name("y(t)");
+                ui.scope(|ui| {
+                    plot_theme.apply(ui);
+                    ui.add(
+                        Plot::new("fourier_signals_plot")
+                            .line(x_line)
+                            .line(y_line)
+                            .vline(VLine::new(local_t))
+                            .height(128.0),
+                    );
+                });
+
+                if ui.button("Copy as CSV").clicked() {
+                    let mut csv = Vec::new();
+                    if write_samples_to(&mut csv, func, *signal_samples).is_ok() {
+                        ui.output().copied_text = String::from_utf8_lossy(&csv).into_owned();
+                    }
                 }
             });
 
-            ui.label(format!("Output: {:.6}", func(local_t)));
+            if let Some(target) = target.as_ref().filter(|t| t.len() > 1 && !morphing) {
+                egui::CollapsingHeader::new("Residual").show(ui, |ui| {
+                    // The target is sampled evenly over the whole period
+                    let last = target.len() - 1;
+                    let residuals: Vec<_> = target
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &p)| {
+                            let t = i as f64 / last as f64;
+                            (t, (series_fn(t) - p).norm())
+                        })
+                        .collect();
+                    let line = Line::new(Values::from_values_iter(
+                        residuals.iter().map(|&(t, e)| Value::new(t, e)),
+                    ))
+                    .name("|error(t)|");
+                    ui.scope(|ui| {
+                        plot_theme.apply(ui);
+                        ui.add(
+                            Plot::new("fourier_residual_plot")
+                                .line(line)
+                                .vline(VLine::new(local_t))
+                                .include_y(0.0)
+                                .height(128.0),
+                        );
+                    });
+                    let worst = residuals.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+                    if let Some(&(t, e)) = worst {
+                        ui.label(format!("Largest error: {:.6} at t = {:.4}", e, t));
+                    }
+                });
+            }
 
-            const ITERATE_COUNT: usize = 1000;
-            let lines_iter = (0..=ITERATE_COUNT).map(|i| {
-                let t = i as f64 / ITERATE_COUNT as f64 * local_t;
-                let result = func(t);
-                Value::new(result.re, result.im)
+            egui::CollapsingHeader::new("Harmonics").show(ui, |ui| {
+                // Dominant terms on top
+                let mut harmonics = desc.harmonics();
+                harmonics.sort_by(|a, b| b.1.total_cmp(&a.1));
+                egui::ScrollArea::from_max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new("harmonics_grid")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Frequency");
+                            ui.label("Magnitude");
+                            ui.label("Phase (rad)");
+                            ui.end_row();
+                            for (k, magnitude, phase) in harmonics {
+                                ui.label(k.to_string());
+                                ui.label(format!("{:.6}", magnitude));
+                                ui.label(format!("{:.4}", phase));
+                                ui.end_row();
+                            }
+                        });
+                });
             });
-            let line = Line::new(Values::from_values_iter(lines_iter));
-            // let arrow_origins_iter = (0..=10).map(|i| {
-            //     Value::new(0.0, 0.0)
-            // });
-            // let arrow_tips_iter = (0..=10).map(|i| {
-            //     let t = i as f64 / 10 as f64 * local_t;
-            //     let result = func(t);
-            //     Value::new(result.re, result.im)
-            // });
-            let coefficients_n = desc.as_vec().len();
-            let half_range = ((coefficients_n - 1) / 2) as isize;
-            let mut coefficients: Vec<_> = desc
-                .as_vec()
-                .iter()
-                .enumerate()
-                .map(|(a, b)| (a as isize - half_range, b))
-                .collect();
-            coefficients.sort_by(|&(ida, _), &(idb, _)| {
-                if ida.abs() < idb.abs() {
-                    Ordering::Less
-                } else if ida.abs() > idb.abs() {
-                    Ordering::Greater
-                } else if ida > idb {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
+
+            let trace = trace_cache.trace(&series_fn, (shown_n, trace_samples), local_t);
+            let epicycle_points = visible_epicycle_points(
+                &desc.epicycle_points_in_order(local_t, &epicycle_order[..shown_n]),
+                min_arrow_length,
+            );
+            let to_value = |p| {
+                let p = transform.apply(p);
+                Value::new(p.re, p.im)
+            };
+            let mut plot = plot_decorations.apply(Plot::new("fourier_plot").data_aspect(1.0));
+            if *lock_view {
+                let key = (shown_n, trace_samples);
+                plot = include_bounds(plot, trace_cache.bounds(&series_fn, key), transform);
+                for overlay in overlays.iter_mut() {
+                    let key = (overlay.desc.as_vec().len(), *trace_points);
+                    let bounds = overlay.trace_cache.bounds(overlay.desc.as_fn(), key);
+                    plot = include_bounds(plot, bounds, transform);
+                }
+            }
+            if let Some(target) = target.as_ref().filter(|_| *show_target) {
+                // Added first so that it stays beneath the trace
+                let target_iter = target.iter().cloned().map(to_value);
+                plot = plot.line(
+                    Line::new(Values::from_values_iter(target_iter))
+                        .color(egui::Color32::from_rgba_unmultiplied(128, 128, 128, 160)),
+                );
+            }
+            if *show_trace {
+                // Same color as the trace in exported images
+                for line in trace_lines(&trace, transform, *gradient_trace, TRACE_COLOR) {
+                    plot = plot.line(line.width(stroke_style.trace_width));
                 }
+            }
+            if *show_arrows && arrow_opacity > 0.0 {
+                plot = plot.arrows(with_color(
+                    stroke_style.apply_to_arrows(epicycle_arrows(
+                        &epicycle_points,
+                        transform,
+                        *center_epicycles,
+                    )),
+                    arrow_color,
+                ));
+            }
+            if *show_circles && arrow_opacity > 0.0 {
+                for line in circle_lines(&epicycle_points, transform, arrow_opacity) {
+                    plot = plot.line(line);
+                }
+            }
+            for (i, overlay) in overlays.iter_mut().enumerate() {
+                let Overlay {
+                    desc,
+                    epicycle_order,
+                    show_epicycles,
+                    trace_cache,
+                } = overlay;
+                let key = (desc.as_vec().len(), *trace_points);
+                let trace = trace_cache.trace(desc.as_fn(), key, local_t);
+                let color = OVERLAY_COLORS[i % OVERLAY_COLORS.len()];
+                if *show_trace {
+                    for line in trace_lines(&trace, transform, *gradient_trace, color) {
+                        plot = plot.line(line.width(stroke_style.trace_width));
+                    }
+                }
+                if *show_epicycles && arrow_opacity > 0.0 {
+                    let epicycle_points = visible_epicycle_points(
+                        &desc.epicycle_points_in_order(local_t, epicycle_order),
+                        min_arrow_length,
+                    );
+                    plot = plot.arrows(with_color(
+                        stroke_style.apply_to_arrows(epicycle_arrows(
+                            &epicycle_points,
+                            transform,
+                            *center_epicycles,
+                        )),
+                        arrow_color,
+                    ));
+                    if *show_circles {
+                        for line in circle_lines(&epicycle_points, transform, arrow_opacity) {
+                            plot = plot.line(line);
+                        }
+                    }
+                }
+            }
+            ui.scope(|ui| {
+                plot_theme.apply(ui);
+                ui.add(plot);
             });
-            let arrows_pre_sum: Vec<_> = coefficients
-                .iter()
-                .map(|x| {
-                    *x.1 * Complex::new(0.0, local_t * x.0 as f64 * 2.0 * std::f64::consts::PI)
-                        .exp()
-                })
-                .scan(Complex::new(0.0, 0.0), |state, x| {
-                    *state += x;
-                    Some(Value::new(state.re, state.im))
-                })
-                .collect();
-            let arrow = Arrows::new(
-                Values::from_values_iter(
-                    iter::once(Value::new(0.0, 0.0)).chain(arrows_pre_sum.iter().cloned()),
-                ),
-                Values::from_values_iter(arrows_pre_sum.iter().cloned()),
-            );
-            ui.add(
-                Plot::new("fourier_plot")
-                    .line(line)
-                    .arrows(arrow)
-                    .data_aspect(1.0),
-            );
         } else {
             ui.label("Error: Fourier series data is invalid or not set.");
         }
+
+        if let Some(index) = shown_entry {
+            self.show_playlist_entry(index);
+            self.clock.seek(0.0);
+        }
+        if let Some((target, alpha)) = morph_update {
+            self.morph_target = target;
+            self.morph_alpha = alpha;
+            self.trace_cache.clear();
+        }
+    }
+}
+
+// Untransformed points of the shown series on the grid t = k / samples, computed when the
+// trace first reaches them so that frames only evaluate the series at new points
+#[derive(Default)]
+struct TraceCache {
+    // (visible_n, samples) the points belong to
+    key: Option<(usize, usize)>,
+    points: Vec<Complex<f64>>,
+    // Corners of the bounding box of the whole grid
+    bounds: Option<(Complex<f64>, Complex<f64>)>,
+}
+
+impl TraceCache {
+    fn clear(&mut self) {
+        self.key = None;
+        self.points.clear();
+        self.bounds = None;
+    }
+
+    fn check_key(&mut self, key: (usize, usize)) {
+        if self.key != Some(key) {
+            self.clear();
+            self.key = Some(key);
+        }
+    }
+
+    fn extend_to(&mut self, series_fn: impl Fn(f64) -> Complex<f64>, grid_len: usize) {
+        let samples = self.key.map_or(0, |(_, samples)| samples);
+        while self.points.len() < grid_len {
+            let k = self.points.len();
+            self.points.push(series_fn(k as f64 / samples as f64));
+        }
+    }
+
+    // Return value: (min, max) corners of the bounding box of the whole curve
+    fn bounds(
+        &mut self,
+        series_fn: impl Fn(f64) -> Complex<f64>,
+        key: (usize, usize),
+    ) -> (Complex<f64>, Complex<f64>) {
+        self.check_key(key);
+        if let Some(bounds) = self.bounds {
+            return bounds;
+        }
+        self.extend_to(series_fn, key.1 + 1);
+        let first = self.points[0];
+        let bounds = self.points.iter().fold((first, first), |(min, max), p| {
+            (
+                Complex::new(min.re.min(p.re), min.im.min(p.im)),
+                Complex::new(max.re.max(p.re), max.im.max(p.im)),
+            )
+        });
+        self.bounds = Some(bounds);
+        bounds
+    }
+
+    // Return value: (t, point) pairs on the grid up to t, followed by the point at t itself
+    fn trace(
+        &mut self,
+        series_fn: impl Fn(f64) -> Complex<f64>,
+        key: (usize, usize),
+        t: f64,
+    ) -> Vec<(f64, Complex<f64>)> {
+        self.check_key(key);
+        let samples = key.1;
+        let grid_len = ((t * samples as f64) as usize + 1).min(samples + 1);
+        self.extend_to(&series_fn, grid_len);
+        self.points[..grid_len]
+            .iter()
+            .enumerate()
+            .map(|(k, &p)| (k as f64 / samples as f64, p))
+            .chain(iter::once((t, series_fn(t))))
+            .collect()
     }
 }
 
+const TRACE_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 40, 40);
+// Arrows are otherwise colored by the plot; fading them needs a color to take alpha from
+const ARROW_COLOR: egui::Color32 = egui::Color32::from_rgb(128, 128, 128);
+// Trace colors of overlaid series, cycled through in order
+const OVERLAY_COLORS: [egui::Color32; 4] = [
+    egui::Color32::from_rgb(40, 110, 200),
+    egui::Color32::from_rgb(40, 150, 70),
+    egui::Color32::from_rgb(210, 140, 20),
+    egui::Color32::from_rgb(150, 60, 180),
+];
+
+// A further series animated along with the main one, e.g. one part of a composite drawing
+struct Overlay {
+    desc: FourierSeriesDesc<f64>,
+    epicycle_order: Vec<isize>,
+    show_epicycles: bool,
+    trace_cache: TraceCache,
+}
+
+// Lines of a trace of (t, point) pairs, either in a flat color or as a gradient over t
+fn trace_lines(
+    trace: &[(f64, Complex<f64>)],
+    transform: &PlaneTransform,
+    gradient: bool,
+    color: egui::Color32,
+) -> Vec<Line> {
+    let trace: Vec<_> = trace
+        .iter()
+        .map(|&(t, p)| {
+            let p = transform.apply(p);
+            (t, Value::new(p.re, p.im))
+        })
+        .collect();
+    if gradient {
+        gradient_lines(&trace)
+    } else {
+        vec![Line::new(Values::from_values_iter(trace.iter().map(|&(_, v)| v))).color(color)]
+    }
+}
+
+// Arrows from the origin through the partial sums of the series, or from the first partial
+// sum if from_constant is set, which is the centroid as the constant term comes first
+fn epicycle_arrows(
+    epicycle_points: &[Complex<f64>],
+    transform: &PlaneTransform,
+    from_constant: bool,
+) -> Arrows {
+    let tips: Vec<_> = epicycle_points
+        .iter()
+        .map(|&p| {
+            let p = transform.apply(p);
+            Value::new(p.re, p.im)
+        })
+        .collect();
+    let origins = iter::once(Value::new(0.0, 0.0)).chain(tips.iter().cloned());
+    let skip = usize::from(from_constant && !tips.is_empty());
+    Arrows::new(
+        Values::from_values_iter(origins.skip(skip)),
+        Values::from_values_iter(tips.into_iter().skip(skip)),
+    )
+}
+
+// Return value: the partial sums with those ending arrows shorter than min_length left out,
+// so that each short arrow is merged into the next one and the chain still ends at the pen
+// The constant term is always kept, as circles are drawn around the points after it
+fn visible_epicycle_points(epicycle_points: &[Complex<f64>], min_length: f64) -> Vec<Complex<f64>> {
+    let (first, rest) = match epicycle_points.split_first() {
+        Some(split) => split,
+        None => return vec![],
+    };
+    let mut visible = vec![*first];
+    for (i, &p) in rest.iter().enumerate() {
+        let base = visible[visible.len() - 1];
+        if i + 1 == rest.len() || (p - base).norm() >= min_length {
+            visible.push(p);
+        }
+    }
+    visible
+}
+
+// Circles traced by the tip of each arrow around its base
+fn circle_lines(
+    epicycle_points: &[Complex<f64>],
+    transform: &PlaneTransform,
+    opacity: f64,
+) -> Vec<Line> {
+    // The first term is the constant offset, which does not rotate
+    const CIRCLE_POINTS: usize = 64;
+    let circle_color = egui::Color32::from_rgba_unmultiplied(128, 128, 128, 96);
+    let circle_color = fading_color(circle_color, opacity);
+    epicycle_points
+        .iter()
+        .zip(epicycle_points.iter().skip(1))
+        .map(|(center, tip)| {
+            let radius = (tip - center).norm();
+            let circle_iter = (0..=CIRCLE_POINTS).map(|i| {
+                let angle = i as f64 / CIRCLE_POINTS as f64 * std::f64::consts::TAU;
+                let p = transform.apply(center + Complex::from_polar(radius, angle));
+                Value::new(p.re, p.im)
+            });
+            Line::new(Values::from_values_iter(circle_iter)).color(circle_color)
+        })
+        .collect()
+}
+
+// Return value: color made more transparent by opacity in [0, 1]
+fn fading_color(color: egui::Color32, opacity: f64) -> egui::Color32 {
+    color.linear_multiply(opacity as f32)
+}
+
+fn with_color(arrows: Arrows, color: Option<egui::Color32>) -> Arrows {
+    match color {
+        Some(color) => arrows.color(color),
+        None => arrows,
+    }
+}
+
+// Makes the plot include a bounding box given by its (min, max) corners
+fn include_bounds(
+    mut plot: Plot,
+    (min, max): (Complex<f64>, Complex<f64>),
+    transform: &PlaneTransform,
+) -> Plot {
+    // The transform is linear, so the transformed corners enclose the curve
+    let corners = [
+        min,
+        Complex::new(min.re, max.im),
+        max,
+        Complex::new(max.re, min.im),
+    ];
+    for corner in corners.iter().map(|&c| transform.apply(c)) {
+        plot = plot.include_x(corner.re).include_y(corner.im);
+    }
+    plot
+}
+
+// Number of differently colored pieces the gradient trace is split into
+const GRADIENT_SEGMENTS: usize = 48;
+
+// Splits a trace of (t, point) pairs into lines whose hue follows t, from red at t = 0 to
+// purple at t = 1, so colors stay put while the trace grows
+// Moderate saturation and value keep it readable on both light and dark backgrounds
+fn gradient_lines(trace: &[(f64, Value)]) -> Vec<Line> {
+    let segment_of = |t: f64| ((t * GRADIENT_SEGMENTS as f64) as usize).min(GRADIENT_SEGMENTS - 1);
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for i in 1..=trace.len() {
+        if i < trace.len() && segment_of(trace[i].0) == segment_of(trace[start].0) {
+            continue;
+        }
+        // Pieces share their boundary points so that the trace has no gaps
+        let end = i.min(trace.len() - 1);
+        let segment = segment_of(trace[start].0);
+        let hue = 0.8 * (segment as f32 + 0.5) / GRADIENT_SEGMENTS as f32;
+        let color = egui::color::Hsva::new(hue, 0.75, 0.85, 1.0);
+        let values = trace[start..=end].iter().map(|&(_, v)| v);
+        lines.push(Line::new(Values::from_values_iter(values)).color(color));
+        start = end;
+    }
+    lines
+}
+
+// Asks for a destination and writes the series there as JSON
+// Return value: message describing the outcome, or None if cancelled
+fn save_series(desc: &FourierSeriesDesc<f64>) -> Option<String> {
+    let path = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("coefficients.json")
+        .save_file()?;
+    let result = desc
+        .to_json()
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+    Some(match result {
+        Ok(()) => format!("Saved to {}", path.display()),
+        Err(e) => format!("Failed to save coefficients: {}", e),
+    })
+}
+
+// Asks for a destination and writes the coefficients there as CSV
+// Return value: message describing the outcome, or None if cancelled
+fn export_csv(desc: &FourierSeriesDesc<f64>) -> Option<String> {
+    let path = rfd::FileDialog::new()
+        .add_filter("CSV", &["csv"])
+        .set_file_name("coefficients.csv")
+        .save_file()?;
+    let result = std::fs::File::create(&path).and_then(|file| {
+        let mut writer = std::io::BufWriter::new(file);
+        desc.write_csv(&mut writer)?;
+        writer.flush()
+    });
+    Some(match result {
+        Ok(()) => format!("Exported to {}", path.display()),
+        Err(e) => format!("Failed to export CSV: {}", e),
+    })
+}
+
+// Number of points the exported outline is traced with
+const EXPORT_SVG_SAMPLES: usize = 2000;
+
+// Asks for a destination and writes the traced series there as an SVG path
+// Return value: message describing the outcome, or None if cancelled
+fn export_svg(desc: &FourierSeriesDesc<f64>, transform: &PlaneTransform) -> Option<String> {
+    let path = rfd::FileDialog::new()
+        .add_filter("SVG", &["svg"])
+        .set_file_name("fourier.svg")
+        .save_file()?;
+    Some(
+        match vector::write_svg(&path, desc, EXPORT_SVG_SAMPLES, transform) {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Failed to export SVG: {}", e),
+        },
+    )
+}
+
 impl FourierAnimationWindow {
     pub fn reset(&mut self) {
         self.series_desc = None;
-        self.animate_start_t = None;
-        self.t = 0.0;
+        self.epicycle_order.clear();
+        self.trace_cache.clear();
+        self.approximation_error = None;
+        self.save_msg = None;
+        self.target = None;
+        self.overlays.clear();
+        self.morph_target = None;
+        self.playlist.clear();
+        self.playlist_index = 0;
+        self.clock.reset();
+    }
+
+    // Shows the first entry and the others in turn at the end of each loop
+    pub fn set_playlist(&mut self, playlist: Vec<PlaylistEntry>) {
+        self.playlist = playlist;
+        self.show_playlist_entry(0);
+    }
+
+    fn show_playlist_entry(&mut self, index: usize) {
+        let entry = match self.playlist.get(index) {
+            Some(entry) => entry,
+            None => return,
+        };
+        let (desc, error, target) = (
+            entry.desc.clone(),
+            entry.approximation_error,
+            entry.target.clone(),
+        );
+        self.playlist_index = index;
+        self.set(Some(desc));
+        self.set_approximation_error(error);
+        self.set_target(target);
+    }
+
+    // Animates desc along with the current series, sharing its t
+    pub fn add_series(&mut self, desc: FourierSeriesDesc<f64>) {
+        self.overlays.push(Overlay {
+            epicycle_order: desc.epicycle_order(),
+            desc,
+            show_epicycles: true,
+            trace_cache: Default::default(),
+        });
+    }
+
+    pub fn has_series(&self) -> bool {
+        self.series_desc.is_some()
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.clock.speed()
     }
 
     pub fn set_speed(&mut self, speed: f64) {
-        self.animate_speed = speed;
+        self.clock.set_speed(speed);
     }
 
     pub fn set(&mut self, desc: Option<FourierSeriesDesc<f64>>) {
+        self.visible_n = desc.as_ref().map_or(0, |d| d.as_vec().len());
+        self.epicycle_order = desc.as_ref().map_or_else(Vec::new, |d| d.epicycle_order());
         self.series_desc = desc;
+        self.trace_cache.clear();
+    }
+
+    pub fn set_approximation_error(&mut self, error: Option<f64>) {
+        self.approximation_error = error;
+    }
+
+    pub fn set_target(&mut self, target: Option<Vec<Complex<f64>>>) {
+        self.target = target;
+    }
+
+    pub fn plot_decorations(&self) -> PlotDecorations {
+        self.plot_decorations
+    }
+
+    pub fn set_plot_decorations(&mut self, plot_decorations: PlotDecorations) {
+        self.plot_decorations = plot_decorations;
+    }
+
+    pub fn stroke_style(&self) -> StrokeStyle {
+        self.stroke_style
+    }
+
+    pub fn set_stroke_style(&mut self, stroke_style: StrokeStyle) {
+        self.stroke_style = stroke_style;
+    }
+
+    pub fn set_plot_theme(&mut self, plot_theme: PlotTheme) {
+        self.plot_theme = plot_theme;
+    }
+
+    pub fn set_transform(&mut self, transform: PlaneTransform) {
+        self.transform = transform;
     }
 
     pub fn play(&mut self) {
-        self.animate_start_t = Some(Instant::now());
+        self.clock.play();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.clock.is_playing()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series_fn(t: f64) -> Complex<f64> {
+        Complex::new(0.0, std::f64::consts::TAU * t).exp() + Complex::new(t, 0.0)
     }
 
-    pub fn pause(&mut self) {
-        if let Some(instant) = self.animate_start_t {
-            // Flush of t is necessary
-            self.t = (self.t + instant.elapsed().as_secs_f64() * self.animate_speed).fract();
+    // The points the trace had before caching
+    fn uncached_trace(samples: usize, t: f64) -> Vec<(f64, Complex<f64>)> {
+        let grid_len = ((t * samples as f64) as usize + 1).min(samples + 1);
+        (0..grid_len)
+            .map(|k| k as f64 / samples as f64)
+            .chain(iter::once(t))
+            .map(|t| (t, series_fn(t)))
+            .collect()
+    }
+
+    #[test]
+    fn cached_trace_equals_uncached_one() {
+        let mut cache = TraceCache::default();
+        // Growing as the animation plays, then wrapping around, then changing resolution
+        for &(samples, t) in &[
+            (100, 0.0),
+            (100, 0.123),
+            (100, 0.5),
+            (100, 1.0),
+            (100, 0.25),
+            (37, 0.9),
+        ] {
+            assert_eq!(
+                cache.trace(series_fn, (5, samples), t),
+                uncached_trace(samples, t)
+            );
         }
-        self.animate_start_t = None;
     }
 
-    pub fn is_playing(&self) -> bool {
-        self.animate_start_t.is_some()
+    #[test]
+    fn changed_series_is_not_served_from_cache() {
+        let mut cache = TraceCache::default();
+        cache.trace(|_| Complex::new(1.0, 0.0), (3, 100), 1.0);
+        assert_eq!(
+            cache.trace(series_fn, (5, 100), 1.0),
+            uncached_trace(100, 1.0)
+        );
     }
 }
@@ -1,25 +1,60 @@
+use crate::{
+    app_config::AppConfig,
+    ui::{
+        animation_clock::AnimationClock,
+        plot_theme::{PlotDecorations, PlotTheme, StrokeStyle},
+    },
+    util::{math::arc_length, transform::PlaneTransform},
+};
 use eframe::egui;
-use egui::plot::{Line, Plot, Value, Values};
+use egui::plot::{Line, MarkerShape, Plot, Points, Value, Values};
+use fourier_series_animation::svg_path::{path_length, CmdData};
 use num::complex::Complex;
-use std::time::Instant;
 
-type SvgFnType = dyn Fn(f64) -> Complex<f64>;
+type SvgFnType = dyn Fn(f64) -> Complex<f64> + Send + Sync;
+
+// Chords the arc length is summed over
+const ARC_LENGTH_SAMPLES: usize = 100_000;
 
 pub struct SvgPreviewWindow {
     pub svg_fn: Option<Box<SvgFnType>>,
-    animate_start_t: Option<Instant>,
-    // Progress per second
-    animate_speed: f64,
-    t: f64,
+    segments_count: Option<usize>,
+    // Commands the SVG was parsed into, listed for debugging; empty for other sources
+    commands: Vec<CmdData>,
+    // Total length of the curve, measured once when it is set
+    arc_length: Option<f64>,
+    snap_to_segments: bool,
+    plot_theme: PlotTheme,
+    plot_decorations: PlotDecorations,
+    stroke_style: StrokeStyle,
+    transform: PlaneTransform,
+    trace_points: usize,
+    // Draw the whole curve with a dot at the current point, instead of growing it up to there
+    full_outline: bool,
+    clock: AnimationClock,
 }
 
 impl Default for SvgPreviewWindow {
     fn default() -> Self {
+        Self::new(&AppConfig::default())
+    }
+}
+
+impl SvgPreviewWindow {
+    pub fn new(config: &AppConfig) -> Self {
         Self {
             svg_fn: None,
-            animate_start_t: None,
-            animate_speed: 0.23,
-            t: 0.0,
+            segments_count: None,
+            commands: Vec::new(),
+            arc_length: None,
+            snap_to_segments: false,
+            plot_theme: Default::default(),
+            plot_decorations: Default::default(),
+            stroke_style: Default::default(),
+            transform: Default::default(),
+            trace_points: config.trace_points,
+            full_outline: false,
+            clock: AnimationClock::new(config.preview_speed),
         }
     }
 }
@@ -32,55 +67,102 @@ impl super::Window for SvgPreviewWindow {
     fn ui(&mut self, ui: &mut egui::Ui) {
         let Self {
             svg_fn,
-            animate_start_t,
-            animate_speed,
-            t,
+            segments_count,
+            commands,
+            arc_length,
+            snap_to_segments,
+            plot_theme,
+            plot_decorations,
+            stroke_style,
+            transform,
+            trace_points,
+            full_outline,
+            clock,
         } = self;
 
-        let mut local_t = if let Some(instant) = animate_start_t {
-            (*t + instant.elapsed().as_secs_f64() * *animate_speed).fract()
-        } else {
-            *t
-        };
-
-        if let Some(func) = svg_fn {
+        if let Some(raw_fn) = svg_fn {
+            let func = |t| transform.apply(raw_fn(t));
             ui.horizontal(|ui| {
-                let mut animation_should_stop = false;
-                let animation_running = animate_start_t.is_some();
-                let slider = egui::Slider::new(&mut local_t, 0.0..=1.0).clamp_to_range(true);
-                ui.label("Input of t:");
-
-                if ui.add(slider).changed() {
-                    *animate_start_t = None;
-                    animation_should_stop = true;
+                let slider_response = clock.ui(ui);
+                // Holding Ctrl while dragging snaps temporarily
+                let snap_count =
+                    segments_count.filter(|_| *snap_to_segments || ui.input().modifiers.ctrl);
+                if slider_response.changed() {
+                    if let Some(count) = snap_count {
+                        clock.seek((clock.t() * count as f64).round() / count as f64);
+                    }
                 }
 
-                let control_btn_text = if animation_running { "⏸" } else { "▶" };
-                if ui.button(control_btn_text).clicked() {
-                    if animation_running {
-                        *animate_start_t = None;
-                        animation_should_stop = true;
-                    } else {
-                        *animate_start_t = Some(Instant::now());
-                    }
+                if segments_count.is_some() {
+                    ui.checkbox(snap_to_segments, "Snap to segments");
                 }
 
-                // Flush t where necessary
-                if animation_should_stop {
-                    *t = local_t;
+                if let (Some(count), false) = (snap_count, clock.is_playing()) {
+                    let idx = (clock.t() * count as f64).round() as usize;
+                    ui.label(format!("Segment boundary {}/{}", idx, count));
                 }
             });
 
-            ui.label(format!("Output: {:.6}", func(local_t)));
-
-            const ITERATE_COUNT: usize = 1000;
-            let values_iter = (0..=ITERATE_COUNT).map(|i| {
-                let t = i as f64 / ITERATE_COUNT as f64 * local_t;
+            ui.horizontal(|ui| {
+                clock.speed_ui(ui);
+                clock.duration_ui(ui);
+            });
+            ui.horizontal(|ui| {
+                super::trace_points_ui(ui, trace_points);
+                plot_decorations.ui(ui);
+                ui.checkbox(full_outline, "Full outline")
+                    .on_hover_text("Show the whole curve and mark the current point");
+            });
+            ui.horizontal(|ui| stroke_style.ui(ui, false));
+
+            let local_t = clock.t();
+            super::output_ui(ui, local_t, func(local_t));
+            if let Some(length) = arc_length {
+                ui.label(format!("Arc length: {:.6}", length))
+                    .on_hover_text("Jumps between subpaths are left out");
+            }
+            if !commands.is_empty() {
+                egui::CollapsingHeader::new(format!("Parsed commands ({})", commands.len())).show(
+                    ui,
+                    |ui| {
+                        ui.small("Absolute SVG coordinates, before mirroring");
+                        egui::ScrollArea::from_max_height(200.0).show(ui, |ui| {
+                            egui::Grid::new("commands_grid")
+                                .num_columns(2)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (i, command) in commands.iter().enumerate() {
+                                        ui.label(i.to_string());
+                                        ui.monospace(command.to_string());
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    },
+                );
+            }
+
+            let end_t = if *full_outline { 1.0 } else { local_t };
+            let values_iter = (0..=*trace_points).map(|i| {
+                let t = i as f64 / *trace_points as f64 * end_t;
                 let result = func(t);
                 Value::new(result.re, result.im)
             });
-            let line = Line::new(Values::from_values_iter(values_iter));
-            ui.add(Plot::new("svg_plot").line(line).data_aspect(1.0));
+            let line =
+                Line::new(Values::from_values_iter(values_iter)).width(stroke_style.trace_width);
+            let mut plot = Plot::new("svg_plot").line(line).data_aspect(1.0);
+            if *full_outline {
+                let p = func(local_t);
+                let dot = Points::new(Values::from_values(vec![Value::new(p.re, p.im)]))
+                    .shape(MarkerShape::Circle)
+                    .filled(true)
+                    .radius(4.0);
+                plot = plot.points(dot);
+            }
+            ui.scope(|ui| {
+                plot_theme.apply(ui);
+                ui.add(plot_decorations.apply(plot));
+            });
         } else {
             ui.label("Error: SVG is invalid or not set.");
         }
@@ -90,31 +172,67 @@ impl super::Window for SvgPreviewWindow {
 impl SvgPreviewWindow {
     pub fn reset(&mut self) {
         self.svg_fn = None;
-        self.animate_start_t = None;
-        self.t = 0.0;
+        self.segments_count = None;
+        self.commands.clear();
+        self.arc_length = None;
+        self.clock.reset();
     }
 
     pub fn set(&mut self, svg_fn: Option<Box<SvgFnType>>) {
+        // Mirroring keeps lengths, so the untransformed curve is measured
+        // Replaced by the length of the commands if they are known, which skips jumps
+        self.arc_length = svg_fn.as_ref().map(|f| arc_length(f, ARC_LENGTH_SAMPLES));
         self.svg_fn = svg_fn;
     }
 
-    pub fn set_speed(&mut self, speed: f64) {
-        self.animate_speed = speed;
+    pub fn set_segments_count(&mut self, segments_count: Option<usize>) {
+        self.segments_count = segments_count;
     }
 
-    pub fn play(&mut self) {
-        self.animate_start_t = Some(Instant::now());
+    pub fn set_commands(&mut self, commands: Vec<CmdData>) {
+        if !commands.is_empty() {
+            self.arc_length = Some(path_length(&commands));
+        }
+        self.commands = commands;
     }
 
-    pub fn pause(&mut self) {
-        if let Some(instant) = self.animate_start_t {
-            // Flush of t is necessary
-            self.t = (self.t + instant.elapsed().as_secs_f64() * self.animate_speed).fract();
-        }
-        self.animate_start_t = None;
+    pub fn plot_decorations(&self) -> PlotDecorations {
+        self.plot_decorations
+    }
+
+    pub fn set_plot_decorations(&mut self, plot_decorations: PlotDecorations) {
+        self.plot_decorations = plot_decorations;
+    }
+
+    pub fn stroke_style(&self) -> StrokeStyle {
+        self.stroke_style
+    }
+
+    pub fn set_stroke_style(&mut self, stroke_style: StrokeStyle) {
+        self.stroke_style = stroke_style;
+    }
+
+    pub fn set_plot_theme(&mut self, plot_theme: PlotTheme) {
+        self.plot_theme = plot_theme;
+    }
+
+    pub fn set_transform(&mut self, transform: PlaneTransform) {
+        self.transform = transform;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.clock.speed()
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.clock.set_speed(speed);
+    }
+
+    pub fn play(&mut self) {
+        self.clock.play();
     }
 
     pub fn is_playing(&self) -> bool {
-        self.animate_start_t.is_some()
+        self.clock.is_playing()
     }
 }
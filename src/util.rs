@@ -1 +1,4 @@
+pub mod import;
 pub mod math;
+pub mod samples;
+pub mod transform;
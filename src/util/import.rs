@@ -0,0 +1,74 @@
+use super::math::FourierSeriesDesc;
+use num::Complex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unrecognized coefficient format: {0}")]
+    UnrecognizedFormat(String),
+    #[error("Frequency {0} appears more than once")]
+    DuplicateFrequency(isize),
+    #[error("No coefficients found")]
+    Empty,
+}
+
+// Imports the format used by popular Fourier drawing projects: an array of vectors like
+// `{"freq": -1, "amp": 2.5, "phase": 0.3}`, each contributing amp * e^(i * (2 * pi * freq * t + phase))
+// Entries may come in any order; frequencies missing from the symmetric range are zero
+pub fn series_from_vectors_json(content: &str) -> Result<FourierSeriesDesc<f64>, ImportError> {
+    let value: Value = serde_json::from_str(content)?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| ImportError::UnrecognizedFormat("expected an array of vectors".into()))?;
+
+    let mut terms = BTreeMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| entry.get(name))
+                .and_then(Value::as_f64)
+        };
+        let (freq, amp, phase) = match (
+            field(&["freq", "frequency"]),
+            field(&["amp", "amplitude"]),
+            field(&["phase"]),
+        ) {
+            (Some(freq), Some(amp), Some(phase)) => (freq, amp, phase),
+            _ => {
+                return Err(ImportError::UnrecognizedFormat(format!(
+                    "vector {} lacks numeric freq, amp or phase",
+                    idx
+                )))
+            }
+        };
+        if freq.fract() != 0.0 {
+            return Err(ImportError::UnrecognizedFormat(format!(
+                "vector {} has non-integer frequency {}",
+                idx, freq
+            )));
+        }
+
+        let freq = freq as isize;
+        if terms
+            .insert(freq, Complex::from_polar(amp, phase))
+            .is_some()
+        {
+            return Err(ImportError::DuplicateFrequency(freq));
+        }
+    }
+
+    let half_range = terms
+        .keys()
+        .map(|k| k.abs())
+        .max()
+        .ok_or(ImportError::Empty)?;
+    let coefficients = (-half_range..=half_range)
+        .map(|k| terms.get(&k).copied().unwrap_or_default())
+        .collect();
+
+    Ok(FourierSeriesDesc::from_coefficients(coefficients))
+}
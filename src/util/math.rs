@@ -1,8 +1,14 @@
-use num::{traits::NumOps, Complex, Float, Num};
+use num::{Complex, Float, Num, NumCast};
+use rayon::prelude::*;
 use std::fmt::Debug;
 use std::{
+    cmp::Reverse,
     iter::Sum,
-    ops::{Add, Mul, RangeInclusive},
+    ops::{Add, Index, Mul, RangeInclusive},
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        Arc,
+    },
 };
 
 pub trait SqrAbs {
@@ -27,35 +33,186 @@ impl<T: SqrAbs + Add> SqrAbs for Complex<T> {
     }
 }
 
-#[derive(Debug)]
+// Coefficients of the frequencies -(n - 1) / 2 to (n - 1) / 2, so n is always odd and the
+// frequencies are symmetric around 0
+// Conversion functions reject an even n with ConvertError::EvenCoefficientCount
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "FourierSeriesDescData<T>",
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
 pub struct FourierSeriesDesc<T: Float> {
     // Contract: coefficients.len() % 2 != 0
     coefficients: Vec<Complex<T>>,
+    // Frequencies whose integration ran out of recursion depth before meeting tolerance
+    unconverged: Vec<isize>,
+    // Estimated absolute quadrature error of each coefficient, in the order of coefficients
+    // Empty if the coefficients were not integrated adaptively
+    integration_errors: Vec<f64>,
 }
 
-// impl<T: Float> Index<isize> for FourierSeriesDesc<T> {
-//     type Output = Complex<T>;
+// Unvalidated form of FourierSeriesDesc as it appears in serialized data
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FourierSeriesDescData<T: Float> {
+    coefficients: Vec<Complex<T>>,
+    #[serde(default)]
+    unconverged: Vec<isize>,
+    #[serde(default)]
+    integration_errors: Vec<f64>,
+}
 
-//     // Panics: If the vec has incorrect len or index is out of range, the function panics
-//     // Index range is [-(n - 1) / 2, (n - 1) / 2]
-//     fn index(&self, index: isize) -> &Self::Output {
-//         let Self { coefficients } = self;
-//         let half_range = ((coefficients.len() - 1) / 2) as isize;
-//         assert!((-half_range..=half_range).contains(&index));
-//         &coefficients[(index + half_range) as usize]
-//     }
-// }
+#[cfg(feature = "serde")]
+impl<T: Float> std::convert::TryFrom<FourierSeriesDescData<T>> for FourierSeriesDesc<T> {
+    type Error = String;
+
+    fn try_from(data: FourierSeriesDescData<T>) -> Result<Self, Self::Error> {
+        let FourierSeriesDescData {
+            coefficients,
+            unconverged,
+            integration_errors,
+        } = data;
+        if coefficients.len().is_multiple_of(2) {
+            return Err(format!(
+                "expected an odd number of coefficients, found {}",
+                coefficients.len()
+            ));
+        }
+        if !integration_errors.is_empty() && integration_errors.len() != coefficients.len() {
+            return Err(format!(
+                "expected {} integration errors, found {}",
+                coefficients.len(),
+                integration_errors.len()
+            ));
+        }
+        Ok(Self {
+            coefficients,
+            unconverged,
+            integration_errors,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Float + serde::Serialize + serde::de::DeserializeOwned> FourierSeriesDesc<T> {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    // Fails if the data is malformed or holds an even number of coefficients
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl<T: Float> Index<isize> for FourierSeriesDesc<T> {
+    type Output = Complex<T>;
+
+    // Panics: If the vec has incorrect len or index is out of range, the function panics
+    // Index range is [-(n - 1) / 2, (n - 1) / 2]
+    fn index(&self, index: isize) -> &Self::Output {
+        let Self { coefficients, .. } = self;
+        assert!(!coefficients.len().is_multiple_of(2));
+        let half_range = ((coefficients.len() - 1) / 2) as isize;
+        assert!(
+            (-half_range..=half_range).contains(&index),
+            "frequency {} is out of range [-{}, {}]",
+            index,
+            half_range,
+            half_range
+        );
+        &coefficients[(index + half_range) as usize]
+    }
+}
+
+impl<T: Float> FourierSeriesDesc<T> {
+    // Coefficients are ordered by frequency from -(n - 1) / 2 to (n - 1) / 2
+    // Panics: If the number of coefficients is even
+    pub fn from_coefficients(coefficients: Vec<Complex<T>>) -> Self {
+        assert!(!coefficients.len().is_multiple_of(2));
+        Self {
+            coefficients,
+            unconverged: Vec::new(),
+            integration_errors: Vec::new(),
+        }
+    }
 
-impl<T: Float> FourierSeriesDesc<T>
-where
-    T: Mul<f64, Output = T>,
-{
     pub fn as_vec(&self) -> &Vec<Complex<T>> {
         &self.coefficients
     }
 
+    // Largest |k| among the terms
+    pub fn max_frequency(&self) -> usize {
+        (self.coefficients.len() - 1) / 2
+    }
+
+    pub fn unconverged_frequencies(&self) -> &[isize] {
+        &self.unconverged
+    }
+
+    // Estimated absolute error of each coefficient, ordered like as_vec
+    // Empty for series that were not computed by adaptive quadrature
+    pub fn integration_errors(&self) -> &[f64] {
+        &self.integration_errors
+    }
+
+    // Largest of integration_errors, None if they are unknown
+    pub fn max_integration_error(&self) -> Option<f64> {
+        self.integration_errors.iter().cloned().reduce(f64::max)
+    }
+
+    // Keeps the central n coefficients, i.e. frequencies up to (n - 1) / 2
+    // Panics: If n is even or larger than the number of coefficients
+    pub fn truncated(&self, n: usize) -> Self {
+        assert!(!n.is_multiple_of(2) && n <= self.coefficients.len());
+        let skip = (self.coefficients.len() - n) / 2;
+        let max_frequency = ((n - 1) / 2) as isize;
+        Self {
+            coefficients: self.coefficients[skip..skip + n].to_vec(),
+            unconverged: self
+                .unconverged
+                .iter()
+                .cloned()
+                .filter(|k| k.abs() <= max_frequency)
+                .collect(),
+            integration_errors: self
+                .integration_errors
+                .get(skip..skip + n)
+                .map_or_else(Vec::new, <[f64]>::to_vec),
+        }
+    }
+
+    // Multiplies every coefficient by the Lanczos sigma factor sinc(k / (m + 1)), m being the
+    // largest |k|, which damps the Gibbs overshoot at corners at the cost of rounder corners
+    pub fn with_lanczos_smoothing(&self) -> Self {
+        let half_range = self.max_frequency() as isize;
+        let width: T = cast((half_range + 1) as f64);
+        let coefficients = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let k = i as isize - half_range;
+                if k == 0 {
+                    return c;
+                }
+                let x = cast::<T>(k as f64 * std::f64::consts::PI) / width;
+                c * (x.sin() / x)
+            })
+            .collect();
+        Self {
+            coefficients,
+            unconverged: self.unconverged.clone(),
+            integration_errors: self.integration_errors.clone(),
+        }
+    }
+
     pub fn as_fn(&self) -> impl Fn(T) -> Complex<T> + '_ {
-        let Self { coefficients } = self;
+        let Self { coefficients, .. } = self;
         let n = coefficients.len();
         move |t| {
             let half_range = ((n - 1) / 2) as isize;
@@ -64,14 +221,181 @@ where
                 .enumerate()
                 .map(|(i, c)| {
                     let i = i as isize - half_range;
-                    *c * Complex::new(T::zero(), t * i as f64 * 2.0 * std::f64::consts::PI).exp()
+                    *c * Complex::new(T::zero(), t * angular_frequency(i)).exp()
                 })
                 .sum()
         }
     }
+
+    // All frequencies by increasing |frequency|, positive frequencies first
+    // The order of a truncated series is a prefix of this, so it can be computed once
+    pub fn epicycle_order(&self) -> Vec<isize> {
+        let half_range = self.max_frequency() as isize;
+        let mut frequencies: Vec<_> = (-half_range..=half_range).collect();
+        frequencies.sort_by_key(|&k| (k.abs(), Reverse(k)));
+        frequencies
+    }
+
+    // Partial sums of the series at t, adding terms by increasing |frequency| with positive
+    // frequencies first; these are the tips of the epicycle arrows
+    pub fn epicycle_points(&self, t: T) -> Vec<Complex<T>> {
+        self.epicycle_points_in_order(t, &self.epicycle_order())
+    }
+
+    // epicycle_points with the terms added in a precomputed order, e.g. from epicycle_order
+    // Panics: If order holds a frequency out of range
+    pub fn epicycle_points_in_order(&self, t: T, order: &[isize]) -> Vec<Complex<T>> {
+        order
+            .iter()
+            .map(|&k| self[k] * Complex::new(T::zero(), t * angular_frequency(k)).exp())
+            .scan(Complex::new(T::zero(), T::zero()), |state, x| {
+                *state = *state + x;
+                Some(*state)
+            })
+            .collect()
+    }
+}
+
+// 2 pi k in the precision of T
+fn angular_frequency<T: Float>(k: isize) -> T {
+    cast(k as f64 * std::f64::consts::TAU)
+}
+
+// Conversion of a constant into the precision of the computation
+fn cast<T: NumCast>(x: f64) -> T {
+    T::from(x).expect("float constants are representable")
+}
+
+// Widening, e.g. to display a series computed in f32
+impl From<FourierSeriesDesc<f32>> for FourierSeriesDesc<f64> {
+    fn from(desc: FourierSeriesDesc<f32>) -> Self {
+        let FourierSeriesDesc {
+            coefficients,
+            unconverged,
+            integration_errors,
+        } = desc;
+        Self {
+            coefficients: coefficients
+                .into_iter()
+                .map(|c| Complex::new(c.re.into(), c.im.into()))
+                .collect(),
+            unconverged,
+            integration_errors,
+        }
+    }
+}
+
+impl FourierSeriesDesc<f64> {
+    // (frequency, magnitude, phase) of every term, in the order of epicycle_points
+    pub fn harmonics(&self) -> Vec<(isize, f64, f64)> {
+        self.epicycle_order()
+            .into_iter()
+            .map(|k| (k, self[k].norm(), self[k].arg()))
+            .collect()
+    }
+
+    // Writes `frequency,real,imag,magnitude,phase` rows sorted by frequency, with the phase in
+    // radians, for spreadsheets and numeric tools
+    pub fn write_csv(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        writeln!(writer, "frequency,real,imag,magnitude,phase")?;
+        let max_frequency = self.max_frequency() as isize;
+        for k in -max_frequency..=max_frequency {
+            let c = self[k];
+            writeln!(writer, "{},{},{},{},{}", k, c.re, c.im, c.norm(), c.arg())?;
+        }
+        Ok(())
+    }
+
+    // Root mean square distance between the series and original at samples evenly spaced t
+    pub fn approximation_error(
+        &self,
+        original: impl Fn(f64) -> Complex<f64>,
+        samples: usize,
+    ) -> f64 {
+        let series_fn = self.as_fn();
+        let sum: f64 = (0..samples)
+            .map(|i| {
+                let t = i as f64 / samples as f64;
+                (series_fn(t) - original(t)).sqr_abs()
+            })
+            .sum();
+        (sum / samples as f64).sqrt()
+    }
+
+    // Computes n coefficients with a discrete Fourier transform of points sampled at
+    // t = i / points.len(), e.g. digitized or hand-drawn curves
+    // Frequencies of at least points.len() / 2 alias, so points should outnumber n
+    // Fails if n is even or exceeds max_n, or if there are no points
+    pub fn from_samples(
+        points: &[Complex<f64>],
+        n: usize,
+        max_n: usize,
+    ) -> Result<Self, ConvertError> {
+        check_n(n, max_n)?;
+        if points.is_empty() {
+            return Err(ConvertError::NoSamples);
+        }
+        let samples_count = points.len();
+        let mut buffer = points.to_vec();
+        rustfft::FftPlanner::new()
+            .plan_fft_forward(samples_count)
+            .process(&mut buffer);
+
+        // Negative frequencies wrap around to the end of the spectrum
+        let half_range = ((n - 1) / 2) as isize;
+        let coefficients = (-half_range..=half_range)
+            .map(|k| buffer[k.rem_euclid(samples_count as isize) as usize] / samples_count as f64)
+            .collect();
+
+        Ok(Self::from_coefficients(coefficients))
+    }
+
+    // Interpolates the coefficients linearly, giving self at alpha = 0 and other at alpha = 1,
+    // so that sweeping alpha morphs one drawing into the other
+    // Panics: If the numbers of coefficients differ
+    pub fn lerp(&self, other: &Self, alpha: f64) -> Self {
+        assert_eq!(
+            self.coefficients.len(),
+            other.coefficients.len(),
+            "series to interpolate must have the same number of coefficients"
+        );
+        let coefficients = self
+            .coefficients
+            .iter()
+            .zip(&other.coefficients)
+            .map(|(a, b)| a + (b - a) * alpha)
+            .collect();
+        Self::from_coefficients(coefficients)
+    }
+
+    // Sum of |c_k|^2, which by Parseval's theorem equals the integral of |f(t)|^2 over [0, 1]
+    // for the full series; a truncated series falls short by the energy of the missing terms
+    pub fn energy(&self) -> f64 {
+        self.coefficients.iter().map(|c| c.norm_sqr()).sum()
+    }
+}
+
+// Integral of |func(t)|^2 over [0, 1], to be compared against FourierSeriesDesc::energy
+// Return value: (energy, whether tolerance was met before running out of depth)
+pub fn function_energy(
+    func: impl Fn(f64) -> Complex<f64> + Clone,
+    tol: f64,
+    max_depth: usize,
+) -> (f64, bool) {
+    integrate_v2(0.0..=1.0, move |t| func(t).norm_sqr(), tol, max_depth)
+}
+
+// Length of the curve traced over [0, 1], summed over chords between samples + 1 evenly
+// spaced points. Jumps of the function, e.g. between SVG subpaths, count as straight lines.
+pub fn arc_length(func: impl Fn(f64) -> Complex<f64>, samples: usize) -> f64 {
+    let points: Vec<_> = (0..=samples)
+        .map(|i| func(i as f64 / samples as f64))
+        .collect();
+    points.windows(2).map(|w| (w[1] - w[0]).norm()).sum()
 }
 
 const X_N_16: usize = 16;
+#[allow(clippy::excessive_precision)]
 const X_POSITIONS_16: [f64; X_N_16] = [
     -0.989400934991649932596,
     -0.944575023073232576078,
@@ -90,6 +414,7 @@ const X_POSITIONS_16: [f64; X_N_16] = [
     0.944575023073232576078,
     0.989400934991649932596,
 ];
+#[allow(clippy::excessive_precision)]
 const X_WEIGHTS_16: [f64; X_N_16] = [
     0.0271524594117540948518,
     0.0622535239386478928628,
@@ -109,88 +434,784 @@ const X_WEIGHTS_16: [f64; X_N_16] = [
     0.0271524594117540948518,
 ];
 
-const TOL: f64 = 1e-5;
+const X_N_32: usize = 32;
+#[allow(clippy::excessive_precision)]
+const X_POSITIONS_32: [f64; X_N_32] = [
+    -0.997263861849481563545,
+    -0.9856115115452683354,
+    -0.964762255587506430774,
+    -0.934906075937739689171,
+    -0.896321155766052123965,
+    -0.849367613732569970134,
+    -0.794483795967942406963,
+    -0.732182118740289680387,
+    -0.663044266930215200975,
+    -0.587715757240762329041,
+    -0.506899908932229390024,
+    -0.421351276130635345364,
+    -0.33186860228212764978,
+    -0.239287362252137074545,
+    -0.144471961582796493485,
+    -0.0483076656877383162348,
+    0.0483076656877383162348,
+    0.144471961582796493485,
+    0.239287362252137074545,
+    0.33186860228212764978,
+    0.421351276130635345364,
+    0.506899908932229390024,
+    0.587715757240762329041,
+    0.663044266930215200975,
+    0.732182118740289680387,
+    0.794483795967942406963,
+    0.849367613732569970134,
+    0.896321155766052123965,
+    0.934906075937739689171,
+    0.964762255587506430774,
+    0.9856115115452683354,
+    0.997263861849481563545,
+];
+#[allow(clippy::excessive_precision)]
+const X_WEIGHTS_32: [f64; X_N_32] = [
+    0.00701861000947009660041,
+    0.0162743947309056706052,
+    0.0253920653092620594558,
+    0.0342738629130214331027,
+    0.0428358980222266806569,
+    0.0509980592623761761962,
+    0.0586840934785355471453,
+    0.0658222227763618468377,
+    0.0723457941088485062254,
+    0.0781938957870703064717,
+    0.0833119242269467552222,
+    0.0876520930044038111428,
+    0.0911738786957638847129,
+    0.0938443990808045656392,
+    0.0956387200792748594191,
+    0.0965400885147278005668,
+    0.0965400885147278005668,
+    0.0956387200792748594191,
+    0.0938443990808045656392,
+    0.0911738786957638847129,
+    0.0876520930044038111428,
+    0.0833119242269467552222,
+    0.0781938957870703064717,
+    0.0723457941088485062254,
+    0.0658222227763618468377,
+    0.0586840934785355471453,
+    0.0509980592623761761962,
+    0.0428358980222266806569,
+    0.0342738629130214331027,
+    0.0253920653092620594558,
+    0.0162743947309056706052,
+    0.00701861000947009660041,
+];
+
+const X_N_64: usize = 64;
+#[allow(clippy::excessive_precision)]
+const X_POSITIONS_64: [f64; X_N_64] = [
+    -0.999305041735772139457,
+    -0.996340116771955279347,
+    -0.991013371476744320739,
+    -0.983336253884625956931,
+    -0.973326827789910963742,
+    -0.961008799652053718919,
+    -0.946411374858402816062,
+    -0.929569172131939575821,
+    -0.910522137078502805756,
+    -0.889315445995114105853,
+    -0.865999398154092819761,
+    -0.840629296252580362752,
+    -0.813265315122797559742,
+    -0.78397235894334140761,
+    -0.752819907260531896612,
+    -0.719881850171610826849,
+    -0.685236313054233242564,
+    -0.648965471254657339858,
+    -0.611155355172393250249,
+    -0.571895646202634034284,
+    -0.531279464019894545658,
+    -0.489403145707052957479,
+    -0.446366017253464087985,
+    -0.402270157963991603696,
+    -0.35722015833766811595,
+    -0.311322871990210956158,
+    -0.264687162208767416374,
+    -0.21742364374000708415,
+    -0.169644420423992818037,
+    -0.12146281929612055447,
+    -0.0729931217877990394495,
+    -0.024350292663424432509,
+    0.024350292663424432509,
+    0.0729931217877990394495,
+    0.12146281929612055447,
+    0.169644420423992818037,
+    0.21742364374000708415,
+    0.264687162208767416374,
+    0.311322871990210956158,
+    0.35722015833766811595,
+    0.402270157963991603696,
+    0.446366017253464087985,
+    0.489403145707052957479,
+    0.531279464019894545658,
+    0.571895646202634034284,
+    0.611155355172393250249,
+    0.648965471254657339858,
+    0.685236313054233242564,
+    0.719881850171610826849,
+    0.752819907260531896612,
+    0.78397235894334140761,
+    0.813265315122797559742,
+    0.840629296252580362752,
+    0.865999398154092819761,
+    0.889315445995114105853,
+    0.910522137078502805756,
+    0.929569172131939575821,
+    0.946411374858402816062,
+    0.961008799652053718919,
+    0.973326827789910963742,
+    0.983336253884625956931,
+    0.991013371476744320739,
+    0.996340116771955279347,
+    0.999305041735772139457,
+];
+#[allow(clippy::excessive_precision)]
+const X_WEIGHTS_64: [f64; X_N_64] = [
+    0.0017832807216964329473,
+    0.00414703326056246763529,
+    0.00650445796897836285612,
+    0.00884675982636394772303,
+    0.0111681394601311288186,
+    0.0134630478967186425981,
+    0.015726030476024719322,
+    0.017951715775697343085,
+    0.0201348231535302093723,
+    0.0222701738083832541593,
+    0.0243527025687108733382,
+    0.0263774697150546586717,
+    0.0283396726142594832275,
+    0.030234657072402478868,
+    0.0320579283548515535855,
+    0.0338051618371416093916,
+    0.0354722132568823838107,
+    0.0370551285402400460404,
+    0.038550153178615629129,
+    0.0399537411327203413867,
+    0.0412625632426235286102,
+    0.0424735151236535890073,
+    0.0435837245293234533768,
+    0.0445905581637565630601,
+    0.0454916279274181444798,
+    0.046284796581314417296,
+    0.0469681828162100173253,
+    0.0475401657148303086623,
+    0.0479993885964583077281,
+    0.0483447622348029571698,
+    0.0485754674415034269348,
+    0.0486909570091397203834,
+    0.0486909570091397203834,
+    0.0485754674415034269348,
+    0.0483447622348029571698,
+    0.0479993885964583077281,
+    0.0475401657148303086623,
+    0.0469681828162100173253,
+    0.046284796581314417296,
+    0.0454916279274181444798,
+    0.0445905581637565630601,
+    0.0435837245293234533768,
+    0.0424735151236535890073,
+    0.0412625632426235286102,
+    0.0399537411327203413867,
+    0.038550153178615629129,
+    0.0370551285402400460404,
+    0.0354722132568823838107,
+    0.0338051618371416093916,
+    0.0320579283548515535855,
+    0.030234657072402478868,
+    0.0283396726142594832275,
+    0.0263774697150546586717,
+    0.0243527025687108733382,
+    0.0222701738083832541593,
+    0.0201348231535302093723,
+    0.017951715775697343085,
+    0.015726030476024719322,
+    0.0134630478967186425981,
+    0.0111681394601311288186,
+    0.00884675982636394772303,
+    0.00650445796897836285612,
+    0.00414703326056246763529,
+    0.0017832807216964329473,
+];
+
+pub const DEFAULT_TOL: f64 = 1e-5;
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+// Ordinary quadrature, using the 16-point Gauss-Legendre rule
+// The range may be reversed, giving the negated integral as the nodes are mirrored with it
+pub fn integrate<In, Out>(range: RangeInclusive<In>, func: impl Fn(In) -> Out) -> Out
+where
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum,
+{
+    gauss_legendre(range, func, &X_POSITIONS_16, &X_WEIGHTS_16)
+}
 
-// Ordinary quadrature
-pub fn integrate<In: Num + Clone, Out: Num + Clone>(
+// Gauss-Legendre quadrature with the given nodes on [-1, 1]
+fn gauss_legendre<In, Out>(
     range: RangeInclusive<In>,
     func: impl Fn(In) -> Out,
+    positions: &[f64],
+    weights: &[f64],
 ) -> Out
 where
-    In: Mul<f64, Output = In>,
-    Out: Mul<f64, Output = Out> + Mul<In, Output = Out> + Sum,
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum,
 {
     let in_two = In::one() + In::one();
     let (start, end) = (range.start().clone(), range.end().clone());
     let half_length = (end.clone() - start.clone()) / in_two.clone();
     let middle = (start + end) / in_two;
-    let result: Out = (0..X_N_16)
-        .map(|n| func(middle.clone() + half_length.clone() * X_POSITIONS_16[n]) * X_WEIGHTS_16[n])
+    let result: Out = positions
+        .iter()
+        .zip(weights)
+        .map(|(&position, &weight)| {
+            func(middle.clone() + half_length.clone() * cast(position)) * cast::<In>(weight)
+        })
         .sum();
     result * half_length
 }
 
-// Adaptive quadrature
-pub fn integrate_v2<In: Num + Clone, Out: Num + Clone>(
+// Subintervals of composite Simpson's rule, costing 2 * SIMPSON_INTERVALS + 1 evaluations
+const SIMPSON_INTERVALS: usize = 16;
+
+// Composite Simpson's rule, with the same signature as integrate, reversed ranges included
+// Being piecewise polynomial, it copes better with kinks of the integrand than
+// Gauss-Legendre, whose accuracy relies on smoothness
+pub fn integrate_simpson<In, Out>(range: RangeInclusive<In>, func: impl Fn(In) -> Out) -> Out
+where
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum,
+{
+    let (start, end) = (range.start().clone(), range.end().clone());
+    let points = 2 * SIMPSON_INTERVALS;
+    let step = (end - start.clone()) * cast(1.0 / points as f64);
+    let result: Out = (0..=points)
+        .map(|i| {
+            let weight = if i == 0 || i == points {
+                1.0
+            } else if i % 2 == 1 {
+                4.0
+            } else {
+                2.0
+            };
+            func(start.clone() + step.clone() * cast(i as f64)) * cast::<In>(weight)
+        })
+        .sum();
+    result * (step / cast(3.0))
+}
+
+// Base rule of the adaptive quadrature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quadrature {
+    // 16-point Gauss-Legendre, very accurate for smooth integrands
+    #[default]
+    GaussLegendre,
+    // Higher orders of Gauss-Legendre, exact for polynomials of degree up to 63 and 127, so
+    // that smooth integrands need fewer subdivisions at the cost of more nodes each
+    GaussLegendre32,
+    GaussLegendre64,
+    // Composite Simpson's rule, more robust around sharp corners
+    Simpson,
+}
+
+impl Quadrature {
+    pub const ALL: [Quadrature; 4] = [
+        Quadrature::GaussLegendre,
+        Quadrature::GaussLegendre32,
+        Quadrature::GaussLegendre64,
+        Quadrature::Simpson,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Quadrature::GaussLegendre => "Gauss-Legendre 16",
+            Quadrature::GaussLegendre32 => "Gauss-Legendre 32",
+            Quadrature::GaussLegendre64 => "Gauss-Legendre 64",
+            Quadrature::Simpson => "Simpson",
+        }
+    }
+
+    // Number of function evaluations of one application of the rule
+    pub fn evaluations(self) -> usize {
+        match self {
+            Quadrature::GaussLegendre => X_N_16,
+            Quadrature::GaussLegendre32 => X_N_32,
+            Quadrature::GaussLegendre64 => X_N_64,
+            Quadrature::Simpson => 2 * SIMPSON_INTERVALS + 1,
+        }
+    }
+
+    pub fn integrate<In, Out>(self, range: RangeInclusive<In>, func: impl Fn(In) -> Out) -> Out
+    where
+        In: Num + Clone + NumCast,
+        Out: Num + Clone + Mul<In, Output = Out> + Sum,
+    {
+        match self {
+            Quadrature::GaussLegendre => integrate(range, func),
+            Quadrature::GaussLegendre32 => {
+                gauss_legendre(range, func, &X_POSITIONS_32, &X_WEIGHTS_32)
+            }
+            Quadrature::GaussLegendre64 => {
+                gauss_legendre(range, func, &X_POSITIONS_64, &X_WEIGHTS_64)
+            }
+            Quadrature::Simpson => integrate_simpson(range, func),
+        }
+    }
+}
+
+// Adaptive quadrature, subdividing at most max_depth times until halves agree with the
+// whole within tol
+// Reversed ranges give the negated integral, as the halves keep the range's direction
+// Return value: (integral, whether tolerance was met before running out of depth)
+pub fn integrate_v2<In, Out>(
     range: RangeInclusive<In>,
     func: impl Fn(In) -> Out + Clone,
-) -> Out
+    tol: f64,
+    max_depth: usize,
+) -> (Out, bool)
+where
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum + SqrAbs,
+{
+    integrate_adaptive(range, func, tol, max_depth, Quadrature::GaussLegendre)
+}
+
+// integrate_v2 with a choice of the base rule
+pub fn integrate_adaptive<In, Out>(
+    range: RangeInclusive<In>,
+    func: impl Fn(In) -> Out + Clone,
+    tol: f64,
+    max_depth: usize,
+    quadrature: Quadrature,
+) -> (Out, bool)
+where
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum + SqrAbs,
+{
+    let (result, _, converged) =
+        integrate_adaptive_with_error(range, func, tol, max_depth, quadrature);
+    (result, converged)
+}
+
+// integrate_adaptive that also estimates the absolute error of the result, summing the
+// disagreement between each accepted interval and its halves
+// Return value: (integral, estimated error, whether tolerance was met before running out of
+// depth)
+pub fn integrate_adaptive_with_error<In, Out>(
+    range: RangeInclusive<In>,
+    func: impl Fn(In) -> Out + Clone,
+    tol: f64,
+    max_depth: usize,
+    quadrature: Quadrature,
+) -> (Out, f64, bool)
 where
-    In: Mul<f64, Output = In>,
-    Out: Mul<f64, Output = Out> + Mul<In, Output = Out> + Sum + SqrAbs,
+    In: Num + Clone + NumCast,
+    Out: Num + Clone + Mul<In, Output = Out> + Sum + SqrAbs,
 {
-    fn inner<In: Num + Clone, Out: Num + Clone>(
+    fn inner<In, Out>(
         range: RangeInclusive<In>,
         func: impl Fn(In) -> Out + Clone,
         last_res: Out,
+        tol: f64,
         avail_depth: usize,
-    ) -> Out
+        quadrature: Quadrature,
+    ) -> (Out, f64, bool)
     where
-        In: Mul<f64, Output = In>,
-        Out: Mul<f64, Output = Out> + Mul<In, Output = Out> + Sum + SqrAbs,
+        In: Num + Clone + NumCast,
+        Out: Num + Clone + Mul<In, Output = Out> + Sum + SqrAbs,
     {
         let in_two = In::one() + In::one();
         let (start, end) = (range.start().clone(), range.end().clone());
         let middle = (start.clone() + end.clone()) / in_two;
         let range_l = start.clone()..=middle.clone();
         let range_r = middle.clone()..=end.clone();
-        let res_l = integrate(range_l.clone(), func.clone());
-        let res_r = integrate(range_r.clone(), func.clone());
+        let res_l = quadrature.integrate(range_l.clone(), func.clone());
+        let res_r = quadrature.integrate(range_r.clone(), func.clone());
 
         let delta = res_l.clone() + res_r.clone() - last_res.clone();
         let delta = delta.sqr_abs().sqrt();
-        if delta <= 15.0 * TOL || avail_depth == 0 {
-            last_res
+        // The halves are far more accurate than the whole, so delta estimates the error of
+        // the whole, which is what gets returned
+        if delta <= 15.0 * tol {
+            (last_res, delta, true)
+        } else if avail_depth == 0 {
+            (last_res, delta, false)
         } else {
-            inner(range_l, func.clone(), res_l, avail_depth - 1)
-                + inner(range_r, func.clone(), res_r, avail_depth - 1)
+            let depth = avail_depth - 1;
+            let (res_l, error_l, converged_l) =
+                inner(range_l, func.clone(), res_l, tol, depth, quadrature);
+            let (res_r, error_r, converged_r) =
+                inner(range_r, func.clone(), res_r, tol, depth, quadrature);
+            (res_l + res_r, error_l + error_r, converged_l && converged_r)
         }
     }
 
-    let last_res = integrate(range.clone(), func.clone());
+    let last_res = quadrature.integrate(range.clone(), func.clone());
+
+    inner(range, func, last_res, tol, max_depth, quadrature)
+}
+
+pub const DEFAULT_MAX_N: usize = 10001;
+
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    // Largest accepted n; bigger requests are rejected instead of running for ages
+    pub max_n: usize,
+    // Absolute error tolerance of the adaptive quadrature for each coefficient
+    pub tolerance: f64,
+    // Recursion limit of the adaptive quadrature; deeper resolves sharper features
+    pub max_depth: usize,
+    pub quadrature: Quadrature,
+    // Incremented each time a coefficient is finished, for reporting progress
+    pub progress: Option<Arc<AtomicUsize>>,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            max_n: DEFAULT_MAX_N,
+            tolerance: DEFAULT_TOL,
+            max_depth: DEFAULT_MAX_DEPTH,
+            quadrature: Quadrature::default(),
+            progress: None,
+        }
+    }
+}
 
-    inner(range, func, last_res, 16)
+#[derive(thiserror::Error, Debug)]
+pub enum ConvertError {
+    #[error("n = {n} exceeds the limit of {max_n} coefficients")]
+    TooManyCoefficients { n: usize, max_n: usize },
+    #[error("n = {0} is even, but a series needs an odd number of coefficients")]
+    EvenCoefficientCount(usize),
+    #[error("No samples to compute the series from")]
+    NoSamples,
 }
 
-pub fn convert_to_fourier_series<T: Float + NumOps>(
-    func: impl Fn(T) -> Complex<T>,
+// Fails if n is even or exceeds max_n
+fn check_n(n: usize, max_n: usize) -> Result<(), ConvertError> {
+    if n.is_multiple_of(2) {
+        return Err(ConvertError::EvenCoefficientCount(n));
+    }
+    if n > max_n {
+        return Err(ConvertError::TooManyCoefficients { n, max_n });
+    }
+    Ok(())
+}
+
+// Lower bound of path function evaluations needed to compute n coefficients
+// (one full and two half quadratures each, assuming no further subdivision)
+pub fn estimate_min_evaluations(n: usize, quadrature: Quadrature) -> usize {
+    n * 3 * quadrature.evaluations()
+}
+
+pub fn convert_to_fourier_series<T>(
+    func: impl Fn(T) -> Complex<T> + Sync,
     n: usize,
-) -> FourierSeriesDesc<T>
+) -> Result<FourierSeriesDesc<T>, ConvertError>
 where
-    Complex<T>: Mul<Complex<f64>, Output = Complex<T>> + Mul<f64, Output = Complex<T>>,
-    T: Mul<f64, Output = T> + SqrAbs,
+    T: Float + SqrAbs + Send,
 {
-    assert!(n % 2 != 0);
+    convert_to_fourier_series_with(func, n, &ConvertOptions::default())
+}
+
+// Fails if n is even or exceeds options.max_n
+pub fn convert_to_fourier_series_with<T>(
+    func: impl Fn(T) -> Complex<T> + Sync,
+    n: usize,
+    options: &ConvertOptions,
+) -> Result<FourierSeriesDesc<T>, ConvertError>
+where
+    T: Float + SqrAbs + Send,
+{
+    check_n(n, options.max_n)?;
     let half_range = ((n - 1) / 2) as isize;
 
-    let mut coefficient_vec = Vec::new();
-    for i in -half_range..=half_range {
-        coefficient_vec.push(integrate_v2(T::zero()..=T::one(), |t| {
-            func(t) * Complex::new(T::zero(), -t * i as f64 * 2.0 * std::f64::consts::PI).exp()
-        }));
+    // Coefficients are independent of each other; collect keeps them in frequency order
+    let results: Vec<_> = (-half_range..=half_range)
+        .into_par_iter()
+        .map(|i| {
+            let result = integrate_adaptive_with_error(
+                T::zero()..=T::one(),
+                |t| func(t) * Complex::new(T::zero(), -t * angular_frequency(i)).exp(),
+                options.tolerance,
+                options.max_depth,
+                options.quadrature,
+            );
+            if let Some(progress) = &options.progress {
+                progress.fetch_add(1, AtomicOrdering::Relaxed);
+            }
+            result
+        })
+        .collect();
+
+    let mut coefficient_vec = Vec::with_capacity(n);
+    let mut unconverged = Vec::new();
+    let mut integration_errors = Vec::with_capacity(n);
+    for (i, (coefficient, error, converged)) in (-half_range..=half_range).zip(results) {
+        coefficient_vec.push(coefficient);
+        integration_errors.push(error);
+        if !converged {
+            unconverged.push(i);
+        }
     }
 
-    FourierSeriesDesc {
+    Ok(FourierSeriesDesc {
         coefficients: coefficient_vec,
+        unconverged,
+        integration_errors,
+    })
+}
+
+// Minimum number of samples taken per coefficient by the FFT conversion
+const FFT_SAMPLES_PER_COEFFICIENT: usize = 8;
+const FFT_MIN_SAMPLES: usize = 1024;
+
+// Computes coefficients from a single FFT of the path sampled on a uniform grid, which is
+// much faster than adaptive quadrature for large n but does not adapt to sharp features
+// Fails if n is even or exceeds DEFAULT_MAX_N
+pub fn convert_to_fourier_series_fft(
+    func: impl Fn(f64) -> Complex<f64>,
+    n: usize,
+) -> Result<FourierSeriesDesc<f64>, ConvertError> {
+    convert_to_fourier_series_sampled(func, n, Sampling::Uniform, DEFAULT_MAX_N)
+}
+
+// Number of samples taken by convert_to_fourier_series_sampled for n coefficients
+fn sampled_count(n: usize) -> usize {
+    n.saturating_mul(FFT_SAMPLES_PER_COEFFICIENT)
+        .max(FFT_MIN_SAMPLES)
+        .checked_next_power_of_two()
+        .unwrap_or(usize::MAX)
+}
+
+// Terms summed by Chebyshev sampling for n coefficients, one per sample and coefficient
+// Uniform sampling needs a single FFT instead, which stays fast for any reasonable n
+pub fn estimate_chebyshev_terms(n: usize) -> usize {
+    n.saturating_mul(sampled_count(n))
+}
+
+// Where the path is sampled by convert_to_fourier_series_sampled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sampling {
+    // Evenly spaced t, transformed with a single FFT
+    #[default]
+    Uniform,
+    // Chebyshev-Lobatto points on every stretch between detected corners, so that samples
+    // land on the corners and cluster around them, integrated with Clenshaw-Curtis weights
+    Chebyshev,
+}
+
+// Sharper turns between consecutive chords of the detection grid count as corners
+const CORNER_ANGLE: f64 = std::f64::consts::PI / 6.0;
+const CORNER_DETECTION_SAMPLES: usize = 4096;
+// Bisection steps locating a corner within a cell of the detection grid, down to about
+// 1e-13 in t
+const CORNER_REFINE_STEPS: usize = 32;
+// Distance from the line before a corner, relative to the length of the path, beyond which
+// the path has left it
+const CORNER_LINE_TOLERANCE: f64 = 1e-12;
+
+// Computes coefficients from samples of the path, taken as chosen by sampling
+// Uniform samples alias the kinks of corners into every coefficient, whereas Chebyshev
+// sampling integrates each smooth stretch between corners separately, converging as fast as
+// on a smooth path; this costs O(n * samples) instead of an FFT
+// Fails if n is even or exceeds max_n
+pub fn convert_to_fourier_series_sampled(
+    func: impl Fn(f64) -> Complex<f64>,
+    n: usize,
+    sampling: Sampling,
+    max_n: usize,
+) -> Result<FourierSeriesDesc<f64>, ConvertError> {
+    check_n(n, max_n)?;
+    let samples_count = sampled_count(n);
+
+    match sampling {
+        Sampling::Uniform => {
+            let samples: Vec<_> = (0..samples_count)
+                .map(|i| func(i as f64 / samples_count as f64))
+                .collect();
+            FourierSeriesDesc::from_samples(&samples, n, max_n)
+        }
+        Sampling::Chebyshev => {
+            // The nodes may run past 1, where the path starts over
+            let samples: Vec<_> = chebyshev_nodes(&detect_corners(&func), samples_count)
+                .into_iter()
+                .map(|(t, weight)| (t, weight * func(t.rem_euclid(1.0))))
+                .collect();
+            let half_range = ((n - 1) / 2) as isize;
+            let coefficients = (-half_range..=half_range)
+                .into_par_iter()
+                .map(|k| {
+                    let omega: f64 = angular_frequency(k);
+                    samples
+                        .iter()
+                        .map(|&(t, weighted)| weighted * Complex::new(0.0, -omega * t).exp())
+                        .sum()
+                })
+                .collect();
+            Ok(FourierSeriesDesc::from_coefficients(coefficients))
+        }
+    }
+}
+
+// Return value: t of every corner in [0, 1), ascending
+fn detect_corners(func: impl Fn(f64) -> Complex<f64>) -> Vec<f64> {
+    let count = CORNER_DETECTION_SAMPLES;
+    let points: Vec<_> = (0..count).map(|i| func(i as f64 / count as f64)).collect();
+    // Chord i runs from point i to point i + 1, wrapping around at the end
+    let chord = |i: usize| points[(i + 1) % count] - points[i % count];
+    let tolerance = CORNER_LINE_TOLERANCE * (0..count).map(|i| chord(i).norm()).sum::<f64>();
+    // A corner within cell i, or on either of its ends, bends the chords next to it
+    let bent = |i: usize| {
+        let (before, after) = (chord(i + count - 1), chord(i + 1));
+        before.norm() > 0.0
+            && after.norm() > 0.0
+            && (after * before.conj()).arg().abs() >= CORNER_ANGLE
+    };
+
+    // Runs of bent cells each hold one corner, so scanning starts after a straight cell
+    let start = match (0..count).find(|&i| !bent(i)) {
+        Some(i) => i + 1,
+        None => return Vec::new(),
+    };
+    let mut corners = Vec::new();
+    let mut i = start;
+    while i < start + count {
+        if !bent(i) {
+            i += 1;
+            continue;
+        }
+        let run_start = i;
+        while bent(i) {
+            i += 1;
+        }
+        // The chord before the run is still straight, so the path follows it up to the corner
+        let (lo, hi) = (run_start as f64 / count as f64, i as f64 / count as f64);
+        let t = refine_corner(&func, lo, hi, chord(run_start + count - 1), tolerance);
+        corners.push(t.rem_euclid(1.0));
+    }
+    corners.sort_by(f64::total_cmp);
+    corners
+}
+
+// Bisects for the point in [lo, hi] where the path leaves the line through func(lo) in
+// direction before, which is exactly the corner if the path is straight up to it
+fn refine_corner(
+    func: impl Fn(f64) -> Complex<f64>,
+    mut lo: f64,
+    mut hi: f64,
+    before: Complex<f64>,
+    tolerance: f64,
+) -> f64 {
+    let start = func(lo);
+    let direction = before / before.norm();
+    for _ in 0..CORNER_REFINE_STEPS {
+        let mid = (lo + hi) / 2.0;
+        // Distance from the line, as the imaginary part of the rotated offset
+        if ((func(mid) - start) * direction.conj()).im.abs() <= tolerance {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+// About count nodes, shared among the stretches between corners by their length, each
+// getting Chebyshev-Lobatto points including both of its ends
+// Return value: (t, quadrature weight) of the nodes, covering one period from the first
+// corner and possibly running past 1
+fn chebyshev_nodes(corners: &[f64], count: usize) -> Vec<(f64, f64)> {
+    // The whole period is one stretch if there are no corners, with t = 0 as its ends
+    let starts = if corners.is_empty() {
+        &[0.0][..]
+    } else {
+        corners
+    };
+    let mut nodes = Vec::with_capacity(count + 2 * starts.len());
+    for (i, &a) in starts.iter().enumerate() {
+        let b = starts.get(i + 1).cloned().unwrap_or(starts[0] + 1.0);
+        let m = (((b - a) * count as f64).round() as usize).max(2);
+        let weights = clenshaw_curtis_weights(m);
+        nodes.extend(weights.into_iter().enumerate().map(|(j, weight)| {
+            let theta = std::f64::consts::PI * j as f64 / m as f64;
+            // Stopping just short of the end takes the left limit where the path jumps
+            let x = if j == m { 1.0 - 1e-9 } else { -theta.cos() };
+            (a + (b - a) * (x + 1.0) / 2.0, weight * (b - a) / 2.0)
+        }));
+    }
+    nodes
+}
+
+// Weights of Clenshaw-Curtis quadrature on [-1, 1] at the points -cos(j pi / m), j = 0..=m
+fn clenshaw_curtis_weights(m: usize) -> Vec<f64> {
+    let half = m / 2;
+    (0..=m)
+        .map(|j| {
+            let theta = std::f64::consts::PI * j as f64 / m as f64;
+            let sum: f64 = (1..=half)
+                .map(|k| {
+                    let b = if 2 * k == m { 1.0 } else { 2.0 };
+                    b / (4.0 * (k * k) as f64 - 1.0) * (2.0 * k as f64 * theta).cos()
+                })
+                .sum();
+            let c = if j == 0 || j == m { 1.0 } else { 2.0 };
+            c / m as f64 * (1.0 - sum)
+        })
+        .collect()
+}
+
+// Finds the smallest odd n whose series is within max_error of original, as measured by
+// FourierSeriesDesc::approximation_error with the given number of samples
+// Series are computed by compute for n = 1, 3, 7, 15, ... up to max_n, and once one is good
+// enough, the n in between are found by bisecting its truncations, assuming the error does
+// not grow with n
+// Return value: (series of the chosen n, whether max_error was met), where the series is the
+// one of max_n if max_error could not be met
+// Panics: If max_n is even
+pub fn fit_series<E>(
+    original: impl Fn(f64) -> Complex<f64>,
+    max_n: usize,
+    max_error: f64,
+    samples: usize,
+    mut compute: impl FnMut(usize) -> Result<FourierSeriesDesc<f64>, E>,
+) -> Result<(FourierSeriesDesc<f64>, bool), E> {
+    assert!(!max_n.is_multiple_of(2));
+    let within =
+        |desc: &FourierSeriesDesc<f64>| desc.approximation_error(&original, samples) <= max_error;
+
+    // Largest n known to fall short
+    let mut short_n = None;
+    let mut n = 1;
+    loop {
+        let desc = compute(n)?;
+        if within(&desc) {
+            // Bisect the odd numbers between short_n and n, n being known to suffice
+            let (mut low, mut high) = (short_n.map_or(0, |m: usize| m / 2 + 1), n / 2);
+            while low < high {
+                let mid = (low + high) / 2;
+                if within(&desc.truncated(mid * 2 + 1)) {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            return Ok((desc.truncated(high * 2 + 1), true));
+        }
+        if n == max_n {
+            return Ok((desc, false));
+        }
+        short_n = Some(n);
+        n = (n * 2 + 1).min(max_n);
     }
 }
@@ -0,0 +1,150 @@
+use num::Complex;
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+// Writes `count + 1` evenly spaced samples of `func` over [0, 1] as `t,x,y` rows
+pub fn write_samples<P: AsRef<Path>>(
+    path: P,
+    func: impl Fn(f64) -> Complex<f64>,
+    count: usize,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    write_samples_to(&mut writer, func, count)?;
+    writer.flush()
+}
+
+pub fn write_samples_to(
+    mut writer: impl Write,
+    func: impl Fn(f64) -> Complex<f64>,
+    count: usize,
+) -> io::Result<()> {
+    writeln!(writer, "t,x,y")?;
+    for i in 0..=count {
+        let t = i as f64 / count as f64;
+        let result = func(t);
+        writeln!(writer, "{},{},{}", t, result.re, result.im)?;
+    }
+    Ok(())
+}
+
+// Coordinates read from a CSV or plain text file
+pub enum SampleData {
+    // `t,x,y` rows as written by write_samples
+    Timed(Vec<(f64, Complex<f64>)>),
+    // `x,y` rows of a closed curve, e.g. a stroke exported by a drawing app, taken to be
+    // evenly spaced over t
+    Points(Vec<Complex<f64>>),
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Reads rows of numbers separated by commas, semicolons or whitespace, skipping blank lines,
+// `#` comments and header lines before the first row
+// Return value: the rows, which all have the same number of columns
+fn read_rows(content: &str) -> io::Result<Vec<Vec<f64>>> {
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Result<Vec<_>, _> = line
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f64>())
+            .collect();
+        match fields {
+            Ok(row) if rows.first().is_none_or(|first| first.len() == row.len()) => rows.push(row),
+            Err(_) if rows.is_empty() => {
+                // Header row
+            }
+            _ => {
+                return Err(invalid_data(format!(
+                    "Invalid row on line {}: `{}`",
+                    line_idx + 1,
+                    line
+                )))
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(invalid_data("No samples found".to_owned()));
+    }
+    Ok(rows)
+}
+
+// Reads `t,x,y` rows, skipping blank lines and a leading header
+// Return value: samples sorted by t
+pub fn read_samples<P: AsRef<Path>>(path: P) -> io::Result<Vec<(f64, Complex<f64>)>> {
+    match read_sample_data(path)? {
+        SampleData::Timed(samples) => Ok(samples),
+        SampleData::Points(_) => Err(invalid_data("Expected `t,x,y` rows".to_owned())),
+    }
+}
+
+// Reads either `t,x,y` samples or an `x,y` point list, telling them apart by column count
+// Return value: timed samples sorted by t, or points in file order
+pub fn read_sample_data<P: AsRef<Path>>(path: P) -> io::Result<SampleData> {
+    let rows = read_rows(&fs::read_to_string(path)?)?;
+    match rows[0].len() {
+        2 => Ok(SampleData::Points(
+            rows.iter().map(|r| Complex::new(r[0], r[1])).collect(),
+        )),
+        3 => {
+            let mut samples: Vec<_> = rows
+                .iter()
+                .map(|r| (r[0], Complex::new(r[1], r[2])))
+                .collect();
+            samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+            Ok(SampleData::Timed(samples))
+        }
+        columns => Err(invalid_data(format!(
+            "Expected 2 (x,y) or 3 (t,x,y) columns, found {}",
+            columns
+        ))),
+    }
+}
+
+// Builds a path function of a closed polygon through the points, with point i at
+// t = i / points.len() as in FourierSeriesDesc::from_samples
+// Panics: If points is empty
+pub fn points_into_proc(
+    points: Vec<Complex<f64>>,
+) -> Box<dyn Fn(f64) -> Complex<f64> + Send + Sync> {
+    assert!(!points.is_empty());
+    Box::new(move |t| {
+        let count = points.len();
+        let idx_prog = t.rem_euclid(1.0) * count as f64;
+        let idx = (idx_prog as usize).min(count - 1);
+        let prog = idx_prog - idx as f64;
+        let (p0, p1) = (points[idx], points[(idx + 1) % count]);
+        p0 + (p1 - p0) * prog
+    })
+}
+
+// Builds a path function linearly interpolating between samples
+pub fn samples_into_proc(
+    samples: Vec<(f64, Complex<f64>)>,
+) -> Box<dyn Fn(f64) -> Complex<f64> + Send + Sync> {
+    Box::new(move |t| {
+        let idx = samples.partition_point(|&(sample_t, _)| sample_t <= t);
+        if idx == 0 {
+            return samples[0].1;
+        }
+        if idx == samples.len() {
+            return samples[idx - 1].1;
+        }
+
+        let (t0, p0) = samples[idx - 1];
+        let (t1, p1) = samples[idx];
+        let prog = (t - t0) / (t1 - t0);
+        p0 + (p1 - p0) * prog
+    })
+}
@@ -0,0 +1,53 @@
+use num::Complex;
+
+// Linear map of the plane written as z -> alpha * z + beta * conj(z)
+// Every real-linear map (mirrors, rotations, transposition, ...) has this form, and the form
+// is closed under composition, so render-time transforms are chained through one type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlaneTransform {
+    alpha: Complex<f64>,
+    beta: Complex<f64>,
+}
+
+impl Default for PlaneTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl PlaneTransform {
+    pub fn identity() -> Self {
+        Self {
+            alpha: Complex::new(1.0, 0.0),
+            beta: Complex::new(0.0, 0.0),
+        }
+    }
+
+    // Negates the real part
+    pub fn mirror_x() -> Self {
+        Self {
+            alpha: Complex::new(0.0, 0.0),
+            beta: Complex::new(-1.0, 0.0),
+        }
+    }
+
+    // Negates the imaginary part; SVG y points downwards while plot y points upwards
+    pub fn mirror_y() -> Self {
+        Self {
+            alpha: Complex::new(0.0, 0.0),
+            beta: Complex::new(1.0, 0.0),
+        }
+    }
+
+    // Applies self first, then other
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            alpha: other.alpha * self.alpha + other.beta * self.beta.conj(),
+            beta: other.alpha * self.beta + other.beta * self.alpha.conj(),
+        }
+    }
+
+    pub fn apply(&self, z: Complex<f64>) -> Complex<f64> {
+        self.alpha * z + self.beta * z.conj()
+    }
+}
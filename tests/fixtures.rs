@@ -0,0 +1,49 @@
+use fourier_series_animation::{
+    svg_path::parse_svg_into_proc, util::math::convert_to_fourier_series,
+};
+
+const N: usize = 101;
+const ERROR_SAMPLES: usize = 1000;
+
+// Converts a fixture end to end and checks the RMS distance of the series from the path
+// Bounds are in SVG units, the fixtures being about 100 units across
+fn assert_reconstructs(name: &str, max_error: f64) {
+    let (func, _) = parse_svg_into_proc(format!("tests/fixtures/{}.svg", name)).unwrap();
+    let desc = convert_to_fourier_series(&func, N).unwrap();
+    let error = desc.approximation_error(&func, ERROR_SAMPLES);
+    assert!(
+        error < max_error,
+        "{}: RMS error {} exceeds {}",
+        name,
+        error,
+        max_error
+    );
+}
+
+// Smooth closed curves converge quickly
+#[test]
+fn arcs_reconstruct() {
+    assert_reconstructs("arcs", 5e-3);
+}
+
+#[test]
+fn beziers_reconstruct() {
+    assert_reconstructs("beziers", 5e-3);
+}
+
+#[test]
+fn transformed_reconstructs() {
+    assert_reconstructs("transformed", 5e-3);
+}
+
+// Corners converge more slowly
+#[test]
+fn lines_reconstruct() {
+    assert_reconstructs("lines", 0.1);
+}
+
+// The pen jumps between the two paths, and the series rings around the jump
+#[test]
+fn multi_path_reconstructs() {
+    assert_reconstructs("multi_path", 10.0);
+}
@@ -0,0 +1,103 @@
+use fourier_series_animation::util::math::{
+    integrate, integrate_adaptive, integrate_simpson, integrate_v2, Quadrature, DEFAULT_MAX_DEPTH,
+    DEFAULT_TOL,
+};
+
+const EPSILON: f64 = 1e-12;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < EPSILON,
+        "expected {}, found {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn reversed_range_negates_constant() {
+    let constant = |_: f64| 3.0;
+    assert_close(integrate(0.0..=1.0, constant), 3.0);
+    assert_close(integrate(1.0..=0.0, constant), -3.0);
+    assert_close(integrate_simpson(0.0..=1.0, constant), 3.0);
+    assert_close(integrate_simpson(1.0..=0.0, constant), -3.0);
+}
+
+#[test]
+fn reversed_range_negates_polynomial() {
+    // Not symmetric within the range, so mirrored nodes with unchanged signs would show
+    let square = |x: f64| x * x;
+    assert_close(integrate(2.0..=0.0, square), -8.0 / 3.0);
+    assert_close(integrate_simpson(2.0..=0.0, square), -8.0 / 3.0);
+}
+
+#[test]
+fn adaptive_reversed_range_negates() {
+    let func = |x: f64| (3.0 * x).sin() + x;
+    let (forward, forward_converged) =
+        integrate_v2(0.0..=1.0, func, DEFAULT_TOL, DEFAULT_MAX_DEPTH);
+    let (backward, backward_converged) =
+        integrate_v2(1.0..=0.0, func, DEFAULT_TOL, DEFAULT_MAX_DEPTH);
+    assert!(forward_converged && backward_converged);
+    assert_close(backward, -forward);
+    assert_close(forward, (1.0 - 3.0_f64.cos()) / 3.0 + 0.5);
+}
+
+#[test]
+fn higher_order_gauss_legendre_is_more_accurate() {
+    // Oscillates too fast for 16 nodes to resolve, yet is smooth
+    const A: f64 = 20.0;
+    let func = |x: f64| x.exp() * (A * x).sin();
+    let antiderivative = |x: f64| x.exp() * ((A * x).sin() - A * (A * x).cos()) / (1.0 + A * A);
+    let exact = antiderivative(3.0) - antiderivative(0.0);
+
+    let error_of = |quadrature: Quadrature| (quadrature.integrate(0.0..=3.0, func) - exact).abs();
+    let error_16 = error_of(Quadrature::GaussLegendre);
+    let error_32 = error_of(Quadrature::GaussLegendre32);
+    let error_64 = error_of(Quadrature::GaussLegendre64);
+    assert!(error_32 < error_16, "{} >= {}", error_32, error_16);
+    assert!(error_64 < error_16, "{} >= {}", error_64, error_16);
+    // Both higher orders reach rounding error, so they are not compared with each other
+    assert!(error_64 < 1e-12, "{}", error_64);
+}
+
+#[test]
+fn every_rule_integrates_constants_exactly() {
+    for quadrature in Quadrature::ALL.iter() {
+        assert_close(quadrature.integrate(-1.0..=1.0, |_: f64| 1.0), 2.0);
+    }
+}
+
+// A path of straight lines traces piecewise linear coordinates, with kinks at the corners
+#[test]
+fn simpson_matches_gauss_legendre_on_piecewise_linear() {
+    // Kinks away from any subdivision point, so that no rule integrates it exactly
+    let func = |x: f64| (x - 0.3).abs() + 2.0 * (x - 0.71).max(0.0);
+    let exact = 0.3 * 0.3 / 2.0 + 0.7 * 0.7 / 2.0 + 0.29 * 0.29;
+
+    // The error estimate is loose around kinks, so the results are checked more coarsely
+    let tol = 1e-10;
+    let (gauss, gauss_converged) = integrate_adaptive(
+        0.0..=1.0,
+        func,
+        tol,
+        DEFAULT_MAX_DEPTH,
+        Quadrature::GaussLegendre,
+    );
+    let (simpson, simpson_converged) =
+        integrate_adaptive(0.0..=1.0, func, tol, DEFAULT_MAX_DEPTH, Quadrature::Simpson);
+    assert!(gauss_converged && simpson_converged);
+    assert!((gauss - exact).abs() < 1e-8, "{} != {}", gauss, exact);
+    assert!((simpson - exact).abs() < 1e-8, "{} != {}", simpson, exact);
+    assert!((gauss - simpson).abs() < 1e-8, "{} != {}", gauss, simpson);
+
+    // Within one linear piece both rules are exact
+    assert_close(
+        Quadrature::Simpson.integrate(0.3..=0.71, func),
+        0.41 * 0.41 / 2.0,
+    );
+    assert_close(
+        Quadrature::GaussLegendre.integrate(0.3..=0.71, func),
+        0.41 * 0.41 / 2.0,
+    );
+}
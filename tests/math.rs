@@ -0,0 +1,379 @@
+use fourier_series_animation::util::math::{
+    convert_to_fourier_series, convert_to_fourier_series_fft, convert_to_fourier_series_sampled,
+    convert_to_fourier_series_with, function_energy, integrate_v2, ConvertError, ConvertOptions,
+    FourierSeriesDesc, Sampling, DEFAULT_MAX_DEPTH, DEFAULT_MAX_N, DEFAULT_TOL,
+};
+use num::Complex;
+use std::f64::consts::TAU;
+
+// Integral of t e^(-i omega t) over [0, 1] for omega = 2 pi k with integer k != 0
+fn exact_ramp_coefficient(omega: f64) -> Complex<f64> {
+    Complex::new(0.0, 1.0 / omega)
+}
+
+#[test]
+fn tighter_tolerance_reduces_error() {
+    // High enough a frequency that a single quadrature does not resolve it
+    let omega = 40.0 * TAU;
+    let exact = exact_ramp_coefficient(omega);
+    let errors: Vec<_> = [1e-1, 1e-3, 1e-5, 1e-11]
+        .iter()
+        .map(|&tol| {
+            let integrand = |t: f64| Complex::new(0.0, -omega * t).exp() * t;
+            let (result, converged) = integrate_v2(0.0..=1.0, integrand, tol, DEFAULT_MAX_DEPTH);
+            assert!(converged);
+            (result - exact).norm()
+        })
+        .collect();
+    for pair in errors.windows(2) {
+        assert!(pair[1] < pair[0], "errors {:?}", errors);
+    }
+    assert!(errors[3] < 1e-12, "errors {:?}", errors);
+}
+#[test]
+fn spiky_integrand_needs_deeper_recursion() {
+    // Narrow Gaussian off any subdivision point, integrating to w sqrt(pi) over [0, 1]
+    let w = 3e-3;
+    let spike = |t: f64| (-((t - 0.3141) / w).powi(2)).exp();
+    let exact = w * std::f64::consts::PI.sqrt();
+
+    let (shallow, shallow_converged) = integrate_v2(0.0..=1.0, spike, 1e-10, 2);
+    assert!(!shallow_converged);
+    assert!(
+        (shallow - exact).abs() > 1e-2 * exact,
+        "shallow {}",
+        shallow
+    );
+
+    let (deep, deep_converged) = integrate_v2(0.0..=1.0, spike, 1e-10, DEFAULT_MAX_DEPTH);
+    assert!(deep_converged);
+    assert!((deep - exact).abs() < 1e-9, "deep {}", deep);
+}
+
+// Circle of radius 2 around 1 + i with a small wobble at frequency -3
+fn wobbly_circle(t: f64) -> Complex<f64> {
+    Complex::new(1.0, 1.0)
+        + 2.0 * Complex::new(0.0, TAU * t).exp()
+        + 0.25 * Complex::new(0.0, -3.0 * TAU * t).exp()
+}
+
+#[test]
+fn fft_matches_quadrature_on_circle() {
+    let n = 21;
+    let fft = convert_to_fourier_series_fft(wobbly_circle, n).unwrap();
+    // Well below the default tolerance, which leaves errors around 1e-8
+    let options = ConvertOptions {
+        tolerance: 1e-12,
+        ..Default::default()
+    };
+    let quadrature = convert_to_fourier_series_with(wobbly_circle, n, &options).unwrap();
+    for (a, b) in fft.as_vec().iter().zip(quadrature.as_vec()) {
+        assert!((a - b).norm() < 1e-9, "fft {}, quadrature {}", a, b);
+    }
+    assert!((fft[1] - 2.0).norm() < 1e-9);
+    assert!((fft[-3] - 0.25).norm() < 1e-9);
+}
+
+#[test]
+fn parallel_coefficients_equal_sequential_ones() {
+    let n = 31;
+    let parallel = convert_to_fourier_series(wobbly_circle, n).unwrap();
+
+    // The same integrals one after another, as before the coefficient loop ran on rayon
+    let half_range = (n / 2) as isize;
+    let sequential: Vec<_> = (-half_range..=half_range)
+        .map(|k| {
+            let omega = k as f64 * TAU;
+            let integrand = |t: f64| wobbly_circle(t) * Complex::new(0.0, -t * omega).exp();
+            integrate_v2(0.0..=1.0, integrand, DEFAULT_TOL, DEFAULT_MAX_DEPTH).0
+        })
+        .collect();
+    assert_eq!(parallel.as_vec(), &sequential);
+
+    // Nor does the number of threads change anything
+    for &threads in &[1, 4] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let pooled = pool.install(|| convert_to_fourier_series(wobbly_circle, n).unwrap());
+        assert_eq!(pooled.as_vec(), parallel.as_vec());
+    }
+}
+
+fn five_terms() -> FourierSeriesDesc<f64> {
+    FourierSeriesDesc::from_coefficients((-2..=2).map(|k| Complex::new(k as f64, 0.0)).collect())
+}
+
+#[test]
+fn index_reaches_both_boundary_frequencies() {
+    let desc = five_terms();
+    assert_eq!(desc[-2], Complex::new(-2.0, 0.0));
+    assert_eq!(desc[0], Complex::new(0.0, 0.0));
+    assert_eq!(desc[2], Complex::new(2.0, 0.0));
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn index_past_highest_frequency_panics() {
+    let _ = five_terms()[3];
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn index_past_lowest_frequency_panics() {
+    let _ = five_terms()[-3];
+}
+
+#[test]
+fn max_n_is_inclusive_and_larger_n_is_an_error() {
+    let options = ConvertOptions {
+        max_n: 7,
+        ..Default::default()
+    };
+    let at_limit = convert_to_fourier_series_with(wobbly_circle, 7, &options).unwrap();
+    assert_eq!(at_limit.as_vec().len(), 7);
+    assert!(matches!(
+        convert_to_fourier_series_with(wobbly_circle, 9, &options),
+        Err(ConvertError::TooManyCoefficients { n: 9, max_n: 7 })
+    ));
+}
+
+// The sampled conversions are capped too, as the Chebyshev one is quadratic in n
+#[test]
+fn sampled_conversions_reject_n_above_max_n() {
+    let is_limit_error = |result: Result<FourierSeriesDesc<f64>, ConvertError>| {
+        matches!(
+            result,
+            Err(ConvertError::TooManyCoefficients { n: 9, max_n: 7 })
+        )
+    };
+    for &sampling in &[Sampling::Uniform, Sampling::Chebyshev] {
+        let at_limit = convert_to_fourier_series_sampled(wobbly_circle, 7, sampling, 7).unwrap();
+        assert_eq!(at_limit.as_vec().len(), 7);
+        assert!(is_limit_error(convert_to_fourier_series_sampled(
+            wobbly_circle,
+            9,
+            sampling,
+            7
+        )));
+    }
+    let points: Vec<_> = (0..16).map(|i| wobbly_circle(i as f64 / 16.0)).collect();
+    let at_limit = FourierSeriesDesc::from_samples(&points, 7, 7).unwrap();
+    assert_eq!(at_limit.as_vec().len(), 7);
+    assert!(is_limit_error(FourierSeriesDesc::from_samples(
+        &points, 9, 7
+    )));
+
+    // Far too many coefficients fail up front instead of overflowing the sample count
+    assert!(matches!(
+        convert_to_fourier_series_fft(wobbly_circle, usize::MAX),
+        Err(ConvertError::TooManyCoefficients {
+            max_n: DEFAULT_MAX_N,
+            ..
+        })
+    ));
+}
+
+// Every conversion rejects an even count instead of panicking
+#[test]
+fn even_n_is_an_error() {
+    let is_even_error = |result: Result<FourierSeriesDesc<f64>, ConvertError>| {
+        matches!(result, Err(ConvertError::EvenCoefficientCount(4)))
+    };
+    assert!(is_even_error(convert_to_fourier_series(wobbly_circle, 4)));
+    assert!(is_even_error(convert_to_fourier_series_fft(
+        wobbly_circle,
+        4
+    )));
+    assert!(is_even_error(convert_to_fourier_series_sampled(
+        wobbly_circle,
+        4,
+        Sampling::Uniform,
+        DEFAULT_MAX_N
+    )));
+    assert!(is_even_error(convert_to_fourier_series_sampled(
+        wobbly_circle,
+        4,
+        Sampling::Chebyshev,
+        DEFAULT_MAX_N
+    )));
+    let points: Vec<_> = (0..16).map(|i| wobbly_circle(i as f64 / 16.0)).collect();
+    assert!(is_even_error(FourierSeriesDesc::from_samples(
+        &points,
+        4,
+        DEFAULT_MAX_N
+    )));
+    assert!(matches!(
+        FourierSeriesDesc::<f64>::from_samples(&[], 5, DEFAULT_MAX_N),
+        Err(ConvertError::NoSamples)
+    ));
+}
+
+#[test]
+fn circle_reconstructs_with_near_zero_error() {
+    let circle = |t: f64| Complex::new(3.0, -1.0) + 5.0 * Complex::new(0.0, TAU * t).exp();
+    // A circle needs frequencies 0 and 1 only, so three terms are exact
+    let desc = convert_to_fourier_series(circle, 3).unwrap();
+    assert!(desc.approximation_error(circle, 1000) < 1e-6);
+
+    // Whereas the constant term alone is off by the radius
+    let constant = desc.truncated(1);
+    assert!((constant.approximation_error(circle, 1000) - 5.0).abs() < 1e-6);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_round_trip_keeps_the_series() {
+    let desc = convert_to_fourier_series(wobbly_circle, 21).unwrap();
+    let json = desc.to_json().unwrap();
+    let read = FourierSeriesDesc::<f64>::from_json(&json).unwrap();
+    assert_eq!(read.as_vec(), desc.as_vec());
+    assert_eq!(
+        read.unconverged_frequencies(),
+        desc.unconverged_frequencies()
+    );
+    assert_eq!(read.integration_errors(), desc.integration_errors());
+    for i in 0..=100 {
+        let t = i as f64 / 100.0;
+        assert_eq!(read.as_fn()(t), desc.as_fn()(t));
+    }
+
+    // Malformed data is rejected rather than breaking the odd count contract
+    let even = r#"{"coefficients": [[1.0, 0.0], [0.0, 1.0]]}"#;
+    assert!(FourierSeriesDesc::<f64>::from_json(even).is_err());
+}
+
+#[test]
+fn circle_has_one_dominant_harmonic() {
+    let phase = 0.7;
+    let circle = |t: f64| Complex::new(-2.0, 4.0) + 3.0 * Complex::new(0.0, TAU * t + phase).exp();
+    // The default tolerance leaves errors around 1e-5 in the vanishing terms
+    let options = ConvertOptions {
+        tolerance: 1e-12,
+        ..Default::default()
+    };
+    let harmonics = convert_to_fourier_series_with(circle, 11, &options)
+        .unwrap()
+        .harmonics();
+    assert_eq!(harmonics.len(), 11);
+    for &(k, magnitude, arg) in &harmonics {
+        match k {
+            0 => assert!((magnitude - 20.0_f64.sqrt()).abs() < 1e-9),
+            1 => {
+                assert!((magnitude - 3.0).abs() < 1e-9);
+                assert!((arg - phase).abs() < 1e-9);
+            }
+            _ => assert!(
+                magnitude < 1e-9,
+                "frequency {} has magnitude {}",
+                k,
+                magnitude
+            ),
+        }
+    }
+}
+
+// Parseval's theorem: a band-limited curve has all its energy in the computed terms
+#[test]
+fn series_energy_equals_function_energy() {
+    let options = ConvertOptions {
+        tolerance: 1e-12,
+        ..Default::default()
+    };
+    let desc = convert_to_fourier_series_with(wobbly_circle, 7, &options).unwrap();
+    let (function_energy, converged) = function_energy(wobbly_circle, 1e-12, DEFAULT_MAX_DEPTH);
+    assert!(converged);
+    // |1 + i|^2 + 2^2 + 0.25^2
+    assert!((function_energy - 6.0625).abs() < 1e-9);
+    assert!(
+        (desc.energy() - function_energy).abs() < 1e-9,
+        "{} != {}",
+        desc.energy(),
+        function_energy
+    );
+    // Dropping the frequency -3 term loses its energy
+    let truncated = desc.truncated(5);
+    assert!((function_energy - truncated.energy() - 0.0625).abs() < 1e-9);
+}
+
+#[test]
+fn circle_samples_give_circle_coefficients() {
+    let samples = 64;
+    let points: Vec<_> = (0..samples)
+        .map(|i| wobbly_circle(i as f64 / samples as f64))
+        .collect();
+    let desc = FourierSeriesDesc::from_samples(&points, 9, DEFAULT_MAX_N).unwrap();
+    // A discrete transform of a band-limited curve is exact up to rounding
+    for k in -4..=4 {
+        let expected = match k {
+            0 => Complex::new(1.0, 1.0),
+            1 => Complex::new(2.0, 0.0),
+            -3 => Complex::new(0.25, 0.0),
+            _ => Complex::new(0.0, 0.0),
+        };
+        assert!(
+            (desc[k] - expected).norm() < 1e-12,
+            "coefficient {} is {}, expected {}",
+            k,
+            desc[k],
+            expected
+        );
+    }
+    assert!(desc.approximation_error(wobbly_circle, 1000) < 1e-12);
+}
+
+#[test]
+fn epicycle_order_is_by_increasing_frequency_positive_first() {
+    let desc = convert_to_fourier_series(wobbly_circle, 7).unwrap();
+    let order = desc.epicycle_order();
+    assert_eq!(order, vec![0, 1, -1, 2, -2, 3, -3]);
+
+    // A truncated series is chained in a prefix of the full order, so the window can keep
+    // the full order and slice it
+    for n in (1..=7).step_by(2) {
+        let truncated = desc.truncated(n);
+        assert_eq!(truncated.epicycle_order(), &order[..n]);
+        let t = 0.37;
+        let in_order = desc.epicycle_points_in_order(t, &order[..n]);
+        let points = truncated.epicycle_points(t);
+        assert_eq!(in_order.len(), points.len());
+        for (a, b) in in_order.iter().zip(&points) {
+            assert!((a - b).norm() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn f32_series_matches_f64_series() {
+    let wobbly_circle_f32 = |t: f32| {
+        let c = wobbly_circle(t as f64);
+        Complex::new(c.re as f32, c.im as f32)
+    };
+    let single = convert_to_fourier_series(wobbly_circle_f32, 9).unwrap();
+    let double = convert_to_fourier_series(wobbly_circle, 9).unwrap();
+    // Both are computed to the same tolerance, single precision rounding being far below it
+    for k in -4..=4 {
+        let widened = Complex::new(single[k].re as f64, single[k].im as f64);
+        assert!(
+            (widened - double[k]).norm() < 1e-4,
+            "coefficient {} is {} in f32, {} in f64",
+            k,
+            widened,
+            double[k]
+        );
+    }
+
+    // Widening keeps the f32 values exactly
+    let widened = FourierSeriesDesc::<f64>::from(single.clone());
+    for k in -4..=4 {
+        assert_eq!(widened[k].re, single[k].re as f64);
+        assert_eq!(widened[k].im, single[k].im as f64);
+    }
+    let (single_fn, widened_fn) = (single.as_fn(), widened.as_fn());
+    for i in 0..=20 {
+        let t = i as f64 / 20.0;
+        let p = single_fn(t as f32);
+        assert!((Complex::new(p.re as f64, p.im as f64) - widened_fn(t)).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,43 @@
+use fourier_series_animation::{
+    render::vector::write_svg,
+    svg_path::{parse_svg_commands, CmdData},
+    util::{math::FourierSeriesDesc, transform::PlaneTransform},
+};
+use num::Complex;
+
+// Coordinates are written with limited precision
+const EPSILON: f64 = 1e-4;
+
+#[test]
+fn exported_svg_parses_back_to_the_traced_points() {
+    let desc = FourierSeriesDesc::from_coefficients(vec![
+        Complex::new(0.5, 0.0),
+        Complex::new(10.0, -2.0),
+        Complex::new(3.0, 0.0),
+    ]);
+    let transform = PlaneTransform::mirror_y();
+    let samples = 64;
+    let path = std::env::temp_dir().join(format!("export-{}.svg", std::process::id()));
+    write_svg(&path, &desc, samples, &transform).unwrap();
+    let commands = parse_svg_commands(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let series_fn = desc.as_fn();
+    let expected: Vec<_> = (0..samples)
+        .map(|i| transform.apply(series_fn(i as f64 / samples as f64)))
+        .collect();
+    // A move to the first point, a line to every other one and the line closing the curve
+    assert_eq!(commands.len(), samples + 1);
+    for (command, expected) in commands.iter().zip(expected.iter().chain(&expected[..1])) {
+        let point = match command {
+            CmdData::Move(p) | CmdData::Line(p) => p,
+            CmdData::CubicCurve(..) => panic!("unexpected curve {}", command),
+        };
+        assert!(
+            (point - expected).norm() < EPSILON,
+            "{} is not {}",
+            point,
+            expected
+        );
+    }
+}
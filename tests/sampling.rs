@@ -0,0 +1,114 @@
+use fourier_series_animation::util::math::{
+    convert_to_fourier_series_sampled, FourierSeriesDesc, Sampling, DEFAULT_MAX_N,
+};
+use num::Complex;
+use std::f64::consts::TAU;
+
+// Corners of an irregular quadrilateral and when the path reaches them, off any power of two
+// grid so that uniform samples miss them
+fn corners() -> Vec<(f64, Complex<f64>)> {
+    vec![
+        (0.0, Complex::new(0.0, 0.0)),
+        (0.2371, Complex::new(3.0, 0.5)),
+        (0.5113, Complex::new(2.5, 2.0)),
+        (0.7789, Complex::new(-0.5, 1.5)),
+    ]
+}
+
+// The closed polygon through corners, moving at constant speed along each side
+fn polygon(t: f64) -> Complex<f64> {
+    let corners = corners();
+    let t = t.rem_euclid(1.0);
+    let i = corners.iter().rposition(|&(start, _)| start <= t).unwrap();
+    let (t0, p0) = corners[i];
+    let (t1, p1) = corners.get(i + 1).cloned().unwrap_or((1.0, corners[0].1));
+    p0 + (p1 - p0) * ((t - t0) / (t1 - t0))
+}
+
+// Coefficients of polygon in closed form: its second derivative is a sum of deltas at the
+// corners, weighted by the change of velocity there
+fn exact_series(n: usize) -> FourierSeriesDesc<f64> {
+    let corners = corners();
+    let velocities: Vec<_> = (0..corners.len())
+        .map(|i| {
+            let (t0, p0) = corners[i];
+            let (t1, p1) = corners.get(i + 1).cloned().unwrap_or((1.0, corners[0].1));
+            (p1 - p0) / (t1 - t0)
+        })
+        .collect();
+    let half_range = ((n - 1) / 2) as isize;
+    let coefficients = (-half_range..=half_range)
+        .map(|k| {
+            if k == 0 {
+                // Mean over each side, weighted by its duration
+                return (0..corners.len())
+                    .map(|i| {
+                        let (t0, p0) = corners[i];
+                        let t1 = corners.get(i + 1).map_or(1.0, |&(t, _)| t);
+                        (p0 + polygon(t1 - 1e-15)) / 2.0 * (t1 - t0)
+                    })
+                    .sum();
+            }
+            let omega = k as f64 * TAU;
+            let sum: Complex<f64> = corners
+                .iter()
+                .enumerate()
+                .map(|(i, &(t, _))| {
+                    let before = velocities[(i + velocities.len() - 1) % velocities.len()];
+                    (velocities[i] - before) * Complex::new(0.0, -omega * t).exp()
+                })
+                .sum();
+            -sum / (omega * omega)
+        })
+        .collect();
+    FourierSeriesDesc::from_coefficients(coefficients)
+}
+
+// Largest distance from the exact truncated series around the second corner, leaving out
+// the ringing of truncation itself, which no sampling can remove
+fn error_near_corner(desc: &FourierSeriesDesc<f64>, exact: &FourierSeriesDesc<f64>) -> f64 {
+    let (corner, _) = corners()[1];
+    let (func, exact_func) = (desc.as_fn(), exact.as_fn());
+    (-50..=50)
+        .map(|i| corner + i as f64 * 1e-4)
+        .map(|t| (func(t) - exact_func(t)).norm())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn chebyshev_sampling_reconstructs_corner_better() {
+    let n = 101;
+    let exact = exact_series(n);
+    let uniform =
+        convert_to_fourier_series_sampled(polygon, n, Sampling::Uniform, DEFAULT_MAX_N).unwrap();
+    let chebyshev =
+        convert_to_fourier_series_sampled(polygon, n, Sampling::Chebyshev, DEFAULT_MAX_N).unwrap();
+
+    let uniform_error = error_near_corner(&uniform, &exact);
+    let chebyshev_error = error_near_corner(&chebyshev, &exact);
+    assert!(
+        chebyshev_error < uniform_error / 100.0,
+        "chebyshev error {}, uniform error {}",
+        chebyshev_error,
+        uniform_error
+    );
+    assert!(
+        chebyshev_error < 1e-9,
+        "chebyshev error {}",
+        chebyshev_error
+    );
+}
+
+#[test]
+fn chebyshev_sampling_matches_uniform_on_smooth_path() {
+    // An ellipse has no corners, so both converge to the same coefficients
+    let ellipse = |t: f64| Complex::new(2.0 * (TAU * t).cos(), (TAU * t).sin());
+    let n = 21;
+    let uniform =
+        convert_to_fourier_series_sampled(ellipse, n, Sampling::Uniform, DEFAULT_MAX_N).unwrap();
+    let chebyshev =
+        convert_to_fourier_series_sampled(ellipse, n, Sampling::Chebyshev, DEFAULT_MAX_N).unwrap();
+    for (u, c) in uniform.as_vec().iter().zip(chebyshev.as_vec()) {
+        assert!((u - c).norm() < 1e-9, "uniform {}, chebyshev {}", u, c);
+    }
+}
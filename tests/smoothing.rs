@@ -0,0 +1,54 @@
+use fourier_series_animation::util::math::{FourierSeriesDesc, DEFAULT_MAX_N};
+use num::Complex;
+
+const SAMPLES: usize = 4096;
+
+// The square with corners at (±1, ±1), traced at constant speed from (1, -1) so that its
+// corners land on the sampling grid
+fn square_samples() -> Vec<Complex<f64>> {
+    let corners = [
+        Complex::new(1.0, -1.0),
+        Complex::new(1.0, 1.0),
+        Complex::new(-1.0, 1.0),
+        Complex::new(-1.0, -1.0),
+    ];
+    let side = SAMPLES / 4;
+    (0..SAMPLES)
+        .map(|i| {
+            let (p0, p1) = (corners[i / side], corners[(i / side + 1) % 4]);
+            p0 + (p1 - p0) * ((i % side) as f64 / side as f64)
+        })
+        .collect()
+}
+
+// Farthest the series strays outside the square
+fn overshoot(desc: &FourierSeriesDesc<f64>) -> f64 {
+    let func = desc.as_fn();
+    (0..SAMPLES)
+        .map(|i| func(i as f64 / SAMPLES as f64))
+        .map(|p| p.re.abs().max(p.im.abs()) - 1.0)
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn lanczos_smoothing_reduces_overshoot_on_square() {
+    let desc = FourierSeriesDesc::from_samples(&square_samples(), 41, DEFAULT_MAX_N).unwrap();
+    let smoothed = desc.with_lanczos_smoothing();
+
+    let (raw, damped) = (overshoot(&desc), overshoot(&smoothed));
+    // Corners of a continuous path ring far less than jumps, but visibly still
+    assert!(raw > 1e-3, "raw overshoot {}", raw);
+    assert!(damped < raw / 4.0, "smoothed {}, raw {}", damped, raw);
+}
+
+#[test]
+fn lanczos_smoothing_keeps_constant_term() {
+    let desc = FourierSeriesDesc::from_samples(&square_samples(), 41, DEFAULT_MAX_N).unwrap();
+    let smoothed = desc.with_lanczos_smoothing();
+    assert_eq!(smoothed[0], desc[0]);
+    // The factors shrink every other term
+    for k in 1..=20 {
+        assert!(smoothed[k].norm() <= desc[k].norm());
+        assert!(smoothed[-k].norm() <= desc[-k].norm());
+    }
+}
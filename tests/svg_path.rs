@@ -0,0 +1,654 @@
+use fourier_series_animation::{
+    svg_path::{
+        cubic_bezier, parse_path_data_into_proc_with, parse_path_data_with,
+        parse_svg_bytes_into_proc_with, parse_svg_commands, parse_svg_into_proc_with,
+        parse_svg_str_into_proc_with, path_length, CmdData, ParseOptions,
+    },
+    util::math::convert_to_fourier_series_fft,
+};
+use num::Complex;
+
+const EPSILON: f64 = 1e-12;
+
+fn assert_close(actual: Complex<f64>, expected: Complex<f64>) {
+    assert!(
+        (actual - expected).norm() < EPSILON,
+        "expected {}, found {}",
+        expected,
+        actual
+    );
+}
+
+// Control points of an asymmetric curve, so that swapped points would show
+fn control_points() -> [Complex<f64>; 4] {
+    [
+        Complex::new(0.0, 0.0),
+        Complex::new(1.0, 3.0),
+        Complex::new(4.0, 3.0),
+        Complex::new(6.0, -1.0),
+    ]
+}
+
+#[test]
+fn cubic_bezier_starts_at_p0() {
+    let [p0, p1, p2, p3] = control_points();
+    assert_close(cubic_bezier(p0, p1, p2, p3, 0.0), p0);
+}
+
+#[test]
+fn cubic_bezier_ends_at_p3() {
+    let [p0, p1, p2, p3] = control_points();
+    assert_close(cubic_bezier(p0, p1, p2, p3, 1.0), p3);
+}
+
+#[test]
+fn cubic_bezier_midpoint() {
+    let [p0, p1, p2, p3] = control_points();
+    // (p0 + 3 p1 + 3 p2 + p3) / 8 = ((0 + 3 + 12 + 6) + (0 + 9 + 9 - 1)i) / 8
+    assert_close(
+        cubic_bezier(p0, p1, p2, p3, 0.5),
+        Complex::new(21.0 / 8.0, 17.0 / 8.0),
+    );
+}
+
+#[test]
+fn single_curve_svg_traces_endpoints() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <path d="M 0 0 C 1 3 4 3 6 -1"/>
+    </svg>"#;
+    let (func, _, warnings) = parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert!(warnings.is_empty());
+
+    let [p0, p1, p2, p3] = control_points();
+    assert_close(func(0.0), p0);
+    assert_close(func(1.0), p3);
+    // The curve is the only segment, so t maps straight to its parameter
+    assert_close(func(0.5), cubic_bezier(p0, p1, p2, p3, 0.5));
+}
+
+#[test]
+fn parse_svg_commands_lists_square() {
+    let commands = parse_svg_commands("tests/fixtures/lines.svg").unwrap();
+    let point = |x, y| Complex::new(x, y);
+    assert_eq!(
+        commands,
+        vec![
+            CmdData::Move(point(10.0, 10.0)),
+            CmdData::Line(point(90.0, 10.0)),
+            CmdData::Line(point(90.0, 90.0)),
+            CmdData::Line(point(10.0, 90.0)),
+            CmdData::Line(point(10.0, 10.0)),
+        ]
+    );
+}
+
+#[test]
+fn path_starting_with_curve_starts_at_first_control_point() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <path d="C 1 3 4 3 6 -1"/>
+    </svg>"#;
+    let (func, _, warnings) = parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert!(warnings.missing_move);
+
+    // Read as if preceded by a move to p1, rather than starting from the origin
+    let [_, p1, p2, p3] = control_points();
+    assert_close(func(0.0), p1);
+    assert_close(func(1.0), p3);
+    assert_close(func(0.5), cubic_bezier(p1, p1, p2, p3, 0.5));
+}
+
+// Traces the d attribute of a single path with default options
+fn trace(data: &str) -> (fourier_series_animation::svg_path::PathFnBox, usize, bool) {
+    let (func, segments_count, warnings) =
+        parse_path_data_into_proc_with(data, &Default::default()).unwrap();
+    (func, segments_count, warnings.missing_move)
+}
+
+#[test]
+fn path_starting_with_relative_curve_starts_at_origin() {
+    let (func, _, missing_move) = trace("c 1 3 4 3 6 -1");
+    assert!(missing_move);
+
+    // Relative to the origin, the points are exactly control_points
+    let [p0, p1, p2, p3] = control_points();
+    assert_close(func(0.0), p0);
+    assert_close(func(1.0), p3);
+    assert_close(func(0.5), cubic_bezier(p0, p1, p2, p3, 0.5));
+}
+
+#[test]
+fn path_starting_with_relative_quadratic_starts_at_origin() {
+    let (func, _, missing_move) = trace("q 2 2 4 0");
+    assert!(missing_move);
+
+    // (p0 + 2 q1 + p3) / 4 at the middle of a quadratic curve
+    assert_close(func(0.0), Complex::new(0.0, 0.0));
+    assert_close(func(0.5), Complex::new(2.0, 1.0));
+    assert_close(func(1.0), Complex::new(4.0, 0.0));
+}
+
+#[test]
+fn path_starting_with_arc_starts_at_origin() {
+    for data in &["a 1 1 0 0 1 2 0", "A 1 1 0 0 1 2 0"] {
+        let (func, segments_count, missing_move) = trace(data);
+        assert!(missing_move);
+        assert!(segments_count > 0, "arc of {} collapsed", data);
+
+        // Half circle around (1, 0) from the origin to (2, 0)
+        assert_close(func(0.0), Complex::new(0.0, 0.0));
+        assert_close(func(1.0), Complex::new(2.0, 0.0));
+        for i in 0..=10 {
+            let radius = (func(i as f64 / 10.0) - Complex::new(1.0, 0.0)).norm();
+            assert!((radius - 1.0).abs() < 1e-3, "radius {} in {}", radius, data);
+        }
+    }
+}
+
+#[test]
+fn path_starting_with_line_starts_at_its_first_point() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <path d="L 1 1 5 1 5 5"/>
+    </svg>"#;
+    let (func, segments_count, warnings) =
+        parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert!(warnings.missing_move);
+
+    // The first pair only names the start, as after a move
+    assert_eq!(segments_count, 2);
+    assert_close(func(0.0), Complex::new(1.0, 1.0));
+    assert_close(func(0.5), Complex::new(5.0, 1.0));
+    assert_close(func(1.0), Complex::new(5.0, 5.0));
+}
+
+#[test]
+fn path_data_parses_without_svg_document() {
+    let data = "M 0 0 l 4 0 L 4 3 z";
+    let (commands, warnings) = parse_path_data_with(data, &Default::default()).unwrap();
+    assert!(warnings.is_empty());
+    let point = |x, y| Complex::new(x, y);
+    assert_eq!(
+        commands,
+        vec![
+            CmdData::Move(point(0.0, 0.0)),
+            CmdData::Line(point(4.0, 0.0)),
+            CmdData::Line(point(4.0, 3.0)),
+            CmdData::Line(point(0.0, 0.0)),
+        ]
+    );
+
+    let (func, segments_count, _) =
+        parse_path_data_into_proc_with(data, &Default::default()).unwrap();
+    assert_eq!(segments_count, 3);
+    assert_close(func(1.0 / 3.0), point(4.0, 0.0));
+    assert_close(func(2.0 / 3.0), point(4.0, 3.0));
+}
+
+#[test]
+fn path_data_without_segments_is_rejected() {
+    assert!(parse_path_data_with("M 1 1", &Default::default()).is_err());
+    assert!(parse_path_data_with("", &Default::default()).is_err());
+}
+
+#[test]
+fn multi_path_svg_traces_both_paths() {
+    let (func, segments_count, _) =
+        parse_svg_into_proc_with("tests/fixtures/multi_path.svg", &Default::default()).unwrap();
+    // Two curves in each path
+    assert_eq!(segments_count, 4);
+
+    // Each path starts and ends at its leftmost point, and passes its rightmost
+    let endpoints = [
+        Complex::new(10.0, 50.0),
+        Complex::new(50.0, 50.0),
+        Complex::new(150.0, 50.0),
+        Complex::new(190.0, 50.0),
+    ];
+    let trace: Vec<_> = (0..=400).map(|i| func(i as f64 / 400.0)).collect();
+    for &point in &endpoints {
+        let distance = trace
+            .iter()
+            .map(|p| (p - point).norm())
+            .fold(f64::INFINITY, f64::min);
+        assert!(distance < EPSILON, "{} is not traced", point);
+    }
+}
+
+#[test]
+fn triangle_of_lines_traces_its_sides() {
+    let (func, segments_count, _) = trace("M 0 0 L 4 0 L 0 3 L 0 0");
+    assert_eq!(segments_count, 3);
+
+    // Each side takes a third of t
+    assert_close(func(0.0), Complex::new(0.0, 0.0));
+    assert_close(func(1.0 / 6.0), Complex::new(2.0, 0.0));
+    assert_close(func(1.0 / 3.0), Complex::new(4.0, 0.0));
+    assert_close(func(0.5), Complex::new(2.0, 1.5));
+    assert_close(func(2.0 / 3.0), Complex::new(0.0, 3.0));
+    assert_close(func(1.0), Complex::new(0.0, 0.0));
+}
+
+// Written with closing tags, which carry no points
+#[test]
+fn triangle_polygon_traces_like_path() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <polygon points="0,0 4,0 0,3"></polygon>
+    </svg>"#;
+    let (polygon, segments_count, _) =
+        parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    let (path, path_count, _) = trace("M 0 0 L 4 0 L 0 3 Z");
+    assert_eq!(segments_count, path_count);
+    for i in 0..=12 {
+        let t = i as f64 / 12.0;
+        assert_close(polygon(t), path(t));
+    }
+
+    let polyline = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <polyline points="0,0 4,0 0,3"></polyline>
+        <path d="M 0 3 L 0 0"></path>
+    </svg>"#;
+    let (polyline, _, _) = parse_svg_str_into_proc_with(polyline, &Default::default()).unwrap();
+    assert_close(polyline(1.0 / 6.0), Complex::new(2.0, 0.0));
+    assert_close(polyline(1.0), Complex::new(0.0, 0.0));
+}
+
+// A full-size background depends on the viewport, so only the path is traced
+#[test]
+fn percentage_background_rect_is_skipped() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <rect width="100%" height="100%" fill="white"/>
+        <path d="M 0 0 L 4 0 L 0 3 Z"/>
+    </svg>"#;
+    let (func, segments_count, warnings) =
+        parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert!(warnings.relative_shape_skipped);
+    let (path, path_count, _) = trace("M 0 0 L 4 0 L 0 3 Z");
+    assert_eq!(segments_count, path_count);
+    for i in 0..=12 {
+        let t = i as f64 / 12.0;
+        assert_close(func(t), path(t));
+    }
+}
+
+// Absolute units are converted at 96 user units per inch
+#[test]
+fn absolute_units_are_converted() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+        <rect x="0.5in" y="12pt" width="1cm" height="6pc"/>
+    </svg>"#;
+    let (func, _, warnings) = parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert!(warnings.is_empty());
+    let points: Vec<_> = (0..=400).map(|i| func(i as f64 / 400.0)).collect();
+    let max = |f: fn(&Complex<f64>) -> f64| points.iter().map(f).fold(f64::MIN, f64::max);
+    let min = |f: fn(&Complex<f64>) -> f64| points.iter().map(f).fold(f64::MAX, f64::min);
+    assert_close(
+        Complex::new(min(|p| p.re), min(|p| p.im)),
+        Complex::new(48.0, 16.0),
+    );
+    assert_close(
+        Complex::new(max(|p| p.re), max(|p| p.im)),
+        Complex::new(48.0 + 96.0 / 2.54, 16.0 + 96.0),
+    );
+}
+
+#[test]
+fn relative_path_traces_like_absolute_one() {
+    let (absolute, absolute_count, _) = trace("M 10 10 L 50 10 C 60 10 70 20 70 30 L 10 30 Z");
+    let (relative, relative_count, _) = trace("m 10 10 l 40 0 c 10 0 20 10 20 20 l -60 0 z");
+    assert_eq!(absolute_count, relative_count);
+    for i in 0..=20 {
+        let t = i as f64 / 20.0;
+        assert_close(relative(t), absolute(t));
+    }
+}
+
+#[test]
+fn quadratic_traces_like_elevated_cubic() {
+    let (quadratic, _, _) = trace("M 0 0 Q 3 6 6 0");
+    // c1 = p0 + 2/3 (q1 - p0), c2 = p3 + 2/3 (q1 - p3)
+    let (cubic, _, _) = trace("M 0 0 C 2 4 4 4 6 0");
+    let (p0, q1, p3) = (
+        Complex::new(0.0, 0.0),
+        Complex::new(3.0, 6.0),
+        Complex::new(6.0, 0.0),
+    );
+    for &t in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+        let expected = (1.0 - t) * (1.0 - t) * p0 + 2.0 * (1.0 - t) * t * q1 + t * t * p3;
+        assert!((quadratic(t) - cubic(t)).norm() < 1e-9);
+        assert!((quadratic(t) - expected).norm() < 1e-9);
+    }
+}
+
+#[test]
+fn close_returns_square_to_its_start() {
+    let (func, segments_count, _) = trace("M 10 10 L 90 10 L 90 90 L 10 90 Z");
+    // Z adds the fourth side
+    assert_eq!(segments_count, 4);
+    assert_close(func(1.0), func(0.0));
+    assert_close(func(0.875), Complex::new(10.0, 50.0));
+}
+
+#[test]
+fn close_returns_to_start_of_latest_subpath() {
+    let (func, _, _) = trace("M 0 0 L 1 0 L 1 1 Z M 5 5 L 6 5 L 6 6 Z");
+    assert_close(func(1.0), Complex::new(5.0, 5.0));
+}
+
+#[test]
+fn quarter_circle_arc_stays_on_radius() {
+    // Counterclockwise in SVG coordinates from angle 0 to 90 degrees around (10, 10)
+    let (func, _, _) = trace("M 15 10 A 5 5 0 0 1 10 15");
+    let center = Complex::new(10.0, 10.0);
+    assert_close(func(0.0), Complex::new(15.0, 10.0));
+    assert_close(func(1.0), Complex::new(10.0, 15.0));
+    for i in 0..=20 {
+        let p = func(i as f64 / 20.0) - center;
+        // The cubic approximation of a quarter circle is off by at most 2.7e-4 of the radius
+        assert!((p.norm() - 5.0).abs() < 5.0 * 3e-4, "radius {}", p.norm());
+        assert!(
+            p.re >= -EPSILON && p.im >= -EPSILON,
+            "{} left the quarter",
+            p
+        );
+    }
+}
+
+#[test]
+fn arc_sweep_flag_picks_other_circle() {
+    // The same endpoints with the opposite sweep bend around (15, 15) instead
+    let (func, _, _) = trace("M 15 10 A 5 5 0 0 0 10 15");
+    let center = Complex::new(15.0, 15.0);
+    for i in 0..=20 {
+        let radius = (func(i as f64 / 20.0) - center).norm();
+        assert!((radius - 5.0).abs() < 5.0 * 3e-4, "radius {}", radius);
+    }
+}
+
+#[test]
+fn garbage_input_is_an_error() {
+    let inputs: [&[u8]; 5] = [
+        &[0xff, 0xfe, 0x00, 0x9c, 0x80, 0x01],
+        b"not an svg at all",
+        b"<svg><path d=\"M 0 0 L",
+        b"<svg><path d=\"M 0 0 L 1 x 2\"/></svg>",
+        b"<svg><<<>>></svg>",
+    ];
+    for input in &inputs {
+        let result = std::panic::catch_unwind(|| {
+            parse_svg_bytes_into_proc_with(input, &Default::default()).is_err()
+        });
+        assert_eq!(
+            result.ok(),
+            Some(true),
+            "{:?}",
+            String::from_utf8_lossy(input)
+        );
+    }
+}
+
+#[test]
+fn arc_length_traces_l_shape_at_constant_speed() {
+    // 30 units across, then 10 down
+    let data = "M 0 0 H 30 V 10";
+    let options = ParseOptions {
+        arc_length: true,
+        ..Default::default()
+    };
+    let (func, _, _) = parse_path_data_into_proc_with(data, &options).unwrap();
+    let steps: Vec<_> = (0..20)
+        .map(|i| (func((i + 1) as f64 / 20.0) - func(i as f64 / 20.0)).norm())
+        .collect();
+    for step in &steps {
+        assert!((step - 2.0).abs() < 0.02, "steps {:?}", steps);
+    }
+    assert_close(func(0.75), Complex::new(30.0, 0.0));
+
+    // Without it, each segment takes half of t whatever its length
+    let (func, _, _) = trace(data);
+    assert_close(func(0.5), Complex::new(30.0, 0.0));
+}
+
+// Scaling multiplies every coefficient, translating only moves the constant term
+#[test]
+fn transformed_path_scales_its_coefficients() {
+    let data = "M 0 0 L 4 0 C 6 2 4 5 1 4 Q 0 3 0 0 Z";
+    let plain = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="{}"/></svg>"#,
+        data
+    );
+    let transformed = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg">
+            <g transform="translate(5 7)"><path transform="scale(3)" d="{}"/></g>
+        </svg>"#,
+        data
+    );
+    let (plain, _, _) = parse_svg_str_into_proc_with(&plain, &Default::default()).unwrap();
+    let (transformed, _, _) =
+        parse_svg_str_into_proc_with(&transformed, &Default::default()).unwrap();
+    // Sampled rather than integrated adaptively, whose absolute tolerance does not scale
+    let plain = convert_to_fourier_series_fft(&plain, 21).unwrap();
+    let transformed = convert_to_fourier_series_fft(&transformed, 21).unwrap();
+
+    for k in -10..=10 {
+        let offset = if k == 0 {
+            Complex::new(5.0, 7.0)
+        } else {
+            Complex::new(0.0, 0.0)
+        };
+        let expected = plain[k] * 3.0 + offset;
+        assert!(
+            (transformed[k] - expected).norm() < 1e-9,
+            "coefficient {} is {}, expected {}",
+            k,
+            transformed[k],
+            expected
+        );
+    }
+}
+
+#[test]
+fn normalized_square_fills_unit_box() {
+    let options = ParseOptions {
+        normalize: true,
+        ..Default::default()
+    };
+    let (commands, _) = parse_path_data_with("M 100 200 H 1100 V 1200 H 100 Z", &options).unwrap();
+    let corners: Vec<_> = commands
+        .iter()
+        .map(|command| match command {
+            CmdData::Move(p) | CmdData::Line(p) => *p,
+            CmdData::CubicCurve(..) => panic!("unexpected curve {}", command),
+        })
+        .collect();
+    let expected = [
+        (-0.5, -0.5),
+        (0.5, -0.5),
+        (0.5, 0.5),
+        (-0.5, 0.5),
+        (-0.5, -0.5),
+    ];
+    assert_eq!(corners.len(), expected.len());
+    for (&corner, &(re, im)) in corners.iter().zip(&expected) {
+        assert_close(corner, Complex::new(re, im));
+    }
+
+    let (func, _, _) =
+        parse_path_data_into_proc_with("M 100 200 H 1100 V 1200 H 100 Z", &options).unwrap();
+    for i in 0..=100 {
+        let p = func(i as f64 / 100.0);
+        assert!(p.re.abs() <= 0.5 + EPSILON && p.im.abs() <= 0.5 + EPSILON);
+    }
+}
+
+#[test]
+fn svg_in_memory_parses_like_file() {
+    for name in &["arcs", "beziers", "lines", "multi_path", "transformed"] {
+        let path = format!("tests/fixtures/{}.svg", name);
+        let content = std::fs::read_to_string(&path).unwrap();
+        let (from_file, file_count, file_warnings) =
+            parse_svg_into_proc_with(&path, &Default::default()).unwrap();
+        let (from_str, str_count, str_warnings) =
+            parse_svg_str_into_proc_with(&content, &Default::default()).unwrap();
+        let (from_bytes, bytes_count, _) =
+            parse_svg_bytes_into_proc_with(content.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(file_count, str_count, "{}", name);
+        assert_eq!(file_count, bytes_count, "{}", name);
+        assert_eq!(
+            file_warnings.to_string(),
+            str_warnings.to_string(),
+            "{}",
+            name
+        );
+        for i in 0..=50 {
+            let t = i as f64 / 50.0;
+            assert_eq!(from_str(t), from_file(t), "{} at t = {}", name, t);
+            assert_eq!(from_bytes(t), from_file(t), "{} at t = {}", name, t);
+        }
+    }
+}
+
+#[test]
+fn connecting_subpaths_replaces_jumps_with_lines() {
+    let data = "M 0 0 L 1 0 M 5 5 L 6 5 M 6 5 L 6 6";
+    let connected = ParseOptions {
+        connect_subpaths: true,
+        ..Default::default()
+    };
+    let (separate, _) = parse_path_data_with(data, &Default::default()).unwrap();
+    assert_eq!(
+        separate,
+        vec![
+            CmdData::Move(Complex::new(0.0, 0.0)),
+            CmdData::Line(Complex::new(1.0, 0.0)),
+            CmdData::Move(Complex::new(5.0, 5.0)),
+            CmdData::Line(Complex::new(6.0, 5.0)),
+            CmdData::Move(Complex::new(6.0, 5.0)),
+            CmdData::Line(Complex::new(6.0, 6.0)),
+        ]
+    );
+    // The move staying in place is dropped rather than becoming an empty line
+    let (joined, _) = parse_path_data_with(data, &connected).unwrap();
+    assert_eq!(
+        joined,
+        vec![
+            CmdData::Move(Complex::new(0.0, 0.0)),
+            CmdData::Line(Complex::new(1.0, 0.0)),
+            CmdData::Line(Complex::new(5.0, 5.0)),
+            CmdData::Line(Complex::new(6.0, 5.0)),
+            CmdData::Line(Complex::new(6.0, 6.0)),
+        ]
+    );
+
+    // Without the jump, neighbouring t stay close together
+    let max_step = |options: &ParseOptions| {
+        let (func, _, _) = parse_path_data_into_proc_with(data, options).unwrap();
+        (0..1000)
+            .map(|i| (func((i + 1) as f64 / 1000.0) - func(i as f64 / 1000.0)).norm())
+            .fold(0.0, f64::max)
+    };
+    assert!(max_step(&Default::default()) > 5.0);
+    assert!(max_step(&connected) < 0.1);
+}
+
+// The path function as it was before segments were indexed: walks the commands on every call
+fn walking_trace(commands: &[CmdData], t: f64) -> Complex<f64> {
+    let segments_count = commands
+        .iter()
+        .filter(|cmd| !matches!(cmd, CmdData::Move(_)))
+        .count();
+    let idx_prog = t * segments_count as f64;
+    let idx = idx_prog as usize;
+    let prog = idx_prog - idx as f64;
+
+    let mut cur_pos = Complex::new(0.0, 0.0);
+    let mut cur_idx = 0;
+    for cmd in commands {
+        let end = match *cmd {
+            CmdData::Move(p0) => {
+                cur_pos = p0;
+                continue;
+            }
+            CmdData::Line(p1) => {
+                if cur_idx == idx {
+                    return cur_pos + (p1 - cur_pos) * prog;
+                }
+                p1
+            }
+            CmdData::CubicCurve(p1, p2, p3) => {
+                if cur_idx == idx {
+                    return cubic_bezier(cur_pos, p1, p2, p3, prog);
+                }
+                p3
+            }
+        };
+        cur_idx += 1;
+        cur_pos = end;
+    }
+    cur_pos
+}
+
+#[test]
+fn indexed_segments_trace_like_walking_commands() {
+    // Subpaths, every kind of segment and a trailing move
+    let data = "M 0 0 L 4 0 C 5 1 5 3 4 4 Q 2 6 0 4 Z M 10 10 a 3 2 30 0 1 5 5 h 2 v -4 M 20 20";
+    let (commands, _) = parse_path_data_with(data, &Default::default()).unwrap();
+    let (func, _, _) = parse_path_data_into_proc_with(data, &Default::default()).unwrap();
+    // Slightly beyond both ends as well
+    for i in -10..=10010 {
+        let t = i as f64 / 10000.0;
+        assert_eq!(func(t), walking_trace(&commands, t), "at t = {}", t);
+    }
+}
+
+#[test]
+fn circle_element_traces_analytic_circle() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <circle cx="3" cy="4" r="2"></circle>
+    </svg>"#;
+    let (func, segments_count, warnings) =
+        parse_svg_str_into_proc_with(svg, &Default::default()).unwrap();
+    assert_eq!(segments_count, 4);
+    assert!(warnings.is_empty());
+
+    let center = Complex::new(3.0, 4.0);
+    let circle = |t: f64| center + 2.0 * Complex::new(0.0, std::f64::consts::TAU * t).exp();
+    // Quarter arcs start and end on the circle, starting on the right of the center
+    for i in 0..=4 {
+        let t = i as f64 / 4.0;
+        assert_close(func(t), circle(t));
+    }
+    // In between, cubic Bezier curves follow the radius closely but the angle only roughly
+    for i in 0..=200 {
+        let t = i as f64 / 200.0;
+        let p = func(t);
+        assert!(
+            ((p - center).norm() - 2.0).abs() < 1e-3,
+            "{} at t = {}",
+            p,
+            t
+        );
+        assert!((p - circle(t)).norm() < 2e-2, "{} at t = {}", p, t);
+    }
+
+    let desc = convert_to_fourier_series_fft(&func, 5).unwrap();
+    assert!((desc[0] - center).norm() < 1e-3);
+    assert!((desc[1] - 2.0).norm() < 1e-3);
+}
+
+#[test]
+fn path_length_leaves_out_jumps() {
+    let (commands, _) = parse_path_data_with(
+        "M 0 0 h 1 v 1 h -1 z M 10 0 h 2 v 2 h -2 z",
+        &Default::default(),
+    )
+    .unwrap();
+    assert!((path_length(&commands) - 12.0).abs() < EPSILON);
+
+    // Connected subpaths draw the jump, so it counts
+    let options = ParseOptions {
+        connect_subpaths: true,
+        ..Default::default()
+    };
+    let (commands, _) =
+        parse_path_data_with("M 0 0 h 1 v 1 h -1 z M 10 0 h 2 v 2 h -2 z", &options).unwrap();
+    assert!((path_length(&commands) - 22.0).abs() < EPSILON);
+}
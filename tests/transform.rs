@@ -0,0 +1,17 @@
+use fourier_series_animation::util::transform::PlaneTransform;
+use num::Complex;
+
+#[test]
+fn flipping_y_maps_svg_y_to_plot_y() {
+    let p = Complex::new(3.0, 10.0);
+    assert_eq!(
+        PlaneTransform::mirror_y().apply(p),
+        Complex::new(3.0, -10.0)
+    );
+    // Flipping twice restores the point
+    let twice = PlaneTransform::mirror_y().then(PlaneTransform::mirror_y());
+    assert_eq!(twice.apply(p), p);
+    // Chained after the horizontal mirror as in the app
+    let both = PlaneTransform::mirror_x().then(PlaneTransform::mirror_y());
+    assert_eq!(both.apply(p), Complex::new(-3.0, -10.0));
+}